@@ -39,9 +39,6 @@ const NINE: u8 = SEGMENT_A | SEGMENT_B | SEGMENT_C | SEGMENT_D | SEGMENT_F | SEG
 const ZERO: u8 = SEGMENT_A | SEGMENT_B | SEGMENT_C | SEGMENT_E | SEGMENT_F | SEGMENT_G;
 
 fn display_as_int(display: u8) -> usize {
-    println!("{:08b}", display);
-    println!("{:08b}", THREE);
-
     match display {
         ZERO => 0,
         ONE => 1,
@@ -59,37 +56,6 @@ fn display_as_int(display: u8) -> usize {
     }
 }
 
-// from day 3
-struct BitIter {
-    num: u8,
-    cur_bit: u32,
-}
-
-impl Iterator for BitIter {
-    type Item = u8;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.cur_bit == u8::BITS {
-            None
-        } else {
-            let res = Some(Self::get_nth_bit_in_word(self.num, self.cur_bit as u32));
-            self.cur_bit += 1;
-
-            res
-        }
-    }
-}
-
-impl BitIter {
-    fn get_nth_bit_in_word(word: u8, n: u32) -> u8 {
-        ((word >> n) & 1) as u8
-    }
-
-    fn new(num: u8) -> Self {
-        BitIter { num, cur_bit: 0 }
-    }
-}
-
 #[derive(Debug)]
 struct SegmentDisplay {
     // segment character to actual segment as u8
@@ -139,80 +105,44 @@ impl SegmentDisplay {
     }
 
     fn string_to_number(&self, s: &str) -> usize {
-        display_as_int(s.chars().fold(0, |acc, x| {
-            println!("{:08b}", acc);
-            acc + self.known_segments.get(&x).unwrap()
-        }))
-    }
-
-    /*
-    // splits a u8 into a vec, where r.1 is the original
-    // bit in the u8, and r.0 is the location of that
-    // bit
-    fn split_apart_u8(mut input: u8) -> Vec<(usize, u8)> {
-        BitIter::new(input)
-            .enumerate()
-            .filter(|v| v.1 == 1)
-            .map(|v| (v.0, 1 << v.0))
-            .collect::<Vec<(usize, u8)>>()
-    }
-
-    // this is just get_nth_bit??? maybe a util module would
-    // be useful here
-    fn is_segment_in_loc(mut disp: u8, loc: u8) -> bool {
-        ((disp >> loc) & 1) > 0
+        display_as_int(
+            s.chars()
+                .fold(0, |acc, x| acc + self.known_segments.get(&x).unwrap()),
+        )
     }
 
-    // counts how many times two segments appear in each
-    // number as listed in nums
-    //
-    // r.0 is the segment, r.1 is the count in nums
-    fn count_two_segments(segs: u8, nums: &[u8]) -> Vec<(u8, usize)> {
-        println!("counting seg split");
-        println!("{:08b}", segs);
-        let split_segs = Self::split_apart_u8(segs);
-        println!("{:?}", split_segs);
-        if split_segs.len() > 2 {
-            panic!("malformed input");
-        }
-
-        let mut res: Vec<(u8, usize)> = Vec::new();
-        for c in &split_segs {
-            let segcount = nums
-                .iter()
-                .filter(|s| Self::is_segment_in_loc(**s, c.0 as u8))
-                .count();
-            res.push((c.1, segcount));
+    fn construct(&mut self, segments: &mut [&str]) {
+        if let Err(e) = self.try_construct(segments) {
+            panic!("malformed input: {}", e);
         }
-
-        res
     }
-    */
 
-    fn construct(&mut self, mut segments: &mut [&str]) {
-        segments.sort_by(|a, b| a.len().cmp(&b.len()));
-
-        /*
-        for i in segments[9].chars().enumerate() {
-            self.segments.insert(i.1, 1 << i.0);
+    // like construct, but anything it can't work with (including ties)
+    // comes back as an error instead, leaving known_segments untouched
+    fn try_construct(&mut self, segments: &mut [&str]) -> Result<(), String> {
+        // every pattern has to be distinct wires, a to g
+        for s in segments.iter() {
+            let mut seen = 0u8;
+            for c in s.chars() {
+                let bit = 1 << wire_index(c).ok_or(format!("`{}` isn't a wire", c))?;
+                if seen & bit != 0 {
+                    return Err(format!("`{}` shows up twice in {}", c, s));
+                }
+                seen |= bit;
+            }
         }
-        */
 
-        /*
-        let segs = segments
-            .iter()
-            .map(|v| self.string_to_display(v))
-            .collect::<Vec<u8>>();
-        */
-
-        /*
-        for s in &segs {
-            println!("{:08b}", s);
+        segments.sort_by(|a, b| a.len().cmp(&b.len()));
+        let mut known = HashMap::new();
+
+        // every digit exactly once, so the lengths are always the same
+        let lengths = segments.iter().map(|s| s.len()).collect::<Vec<usize>>();
+        if lengths != [2, 3, 4, 5, 5, 5, 6, 6, 6, 7] {
+            return Err(format!(
+                "expected one pattern per digit, got lengths {:?}",
+                lengths
+            ));
         }
-        */
-
-        // all unknown characters that we care about at the moment
-        let mut unknowns: Vec<char> = Vec::new();
 
         // at this point:
         // 0 : one
@@ -223,95 +153,55 @@ impl SegmentDisplay {
         acf_seg_str.push_str(segments[0]);
 
         let a_seg_count = elem_count_in_vec(acf_seg_str.as_bytes());
-        println!("a_seg_count: {:?}", a_seg_count);
 
         let a_seg_char = *a_seg_count
             .iter()
             .filter(|v| v.1 <= 1)
             .map(|v| v.0)
             .next()
-            .unwrap() as char;
-
-        println!("a_seg_char: {}", a_seg_char);
-        self.known_segments.insert(a_seg_char, SEGMENT_A);
-
-        /*
-        // this is the 'a' segment
-        let a_seg = self.string_to_display(segments[0]) ^ self.string_to_display(segments[1]);
-        println!("a_seg: {:08b}", a_seg);
-        // the equivalent of segment A
-        self.charsegs.insert(a_seg, SEGMENT_A);
-        */
+            .ok_or("segment a can't be found")? as char;
 
-        // this is segment bd, aka 'the l from four'
-        // this will be useful later, but we don't
-        // currently know what exactly the segment
-        // positions are
+        known.insert(a_seg_char, SEGMENT_A);
 
         // c and f are both located here, but we don't know which one is which
         // so we have to process both of them by checking every display
         // output and counting
-        // let cf_split = Self::split_apart_u8(self.string_to_display(segments[0]));
-
-        /*
-        let mut cf_res: Vec<(usize, usize)> = Vec::new();
-        for c in &cf_split {
-            let segcount = segs
-                .iter()
-                .filter(|s| Self::is_segment_in_loc(**s, c.0 as u8))
-                .count();
-            cf_res.push((c.0, segcount));
-        }
 
-        if cf_res.len() > 2 {
-            panic!("malformed input");
-        }
-        */
-
-        // let cf_res = Self::count_two_segments(self.string_to_display(segments[0]), &segs);
-        let cf_res = Self::count_chars_in_strings(&segments, segments[0]);
-        println!("{:?}", cf_res);
+        let cf_res = Self::count_chars_in_strings(segments, segments[0]);
 
         match cf_res[0].1.cmp(&cf_res[1].1) {
             Ordering::Less => {
-                self.known_segments.insert(cf_res[0].0, SEGMENT_C);
-                self.known_segments.insert(cf_res[1].0, SEGMENT_F);
+                known.insert(cf_res[0].0, SEGMENT_C);
+                known.insert(cf_res[1].0, SEGMENT_F);
             }
             Ordering::Greater => {
-                self.known_segments.insert(cf_res[1].0, SEGMENT_C);
-                self.known_segments.insert(cf_res[0].0, SEGMENT_F);
+                known.insert(cf_res[1].0, SEGMENT_C);
+                known.insert(cf_res[0].0, SEGMENT_F);
             }
             Ordering::Equal => {
-                panic!("malformed input");
+                return Err("segments c and f can't be told apart".to_string());
             }
         };
 
-        /*
-        // eg formed by notting 4 and segment a put together (and 1 << 7)
-        let eg_segs = !(self.string_to_display(segments[2])
-            | self.known_segments.get(&'a').unwrap()
-            | 1 << 7);
-
-        let eg_res = Self::count_two_segments(eg_segs, &segs);
-        */
-
         let mut eg_segs = segments[2].to_string();
         eg_segs.push(a_seg_char);
         let eg_res =
-            Self::count_chars_in_strings(&segments, &Self::xor_string(segments[9], &eg_segs));
-        println!("{:?}", eg_res);
+            Self::count_chars_in_strings(segments, &Self::xor_string(segments[9], &eg_segs));
+        if eg_res.len() != 2 {
+            return Err("segments e and g can't be found".to_string());
+        }
 
         match eg_res[0].1.cmp(&eg_res[1].1) {
             Ordering::Less => {
-                self.known_segments.insert(eg_res[0].0, SEGMENT_E);
-                self.known_segments.insert(eg_res[1].0, SEGMENT_G);
+                known.insert(eg_res[0].0, SEGMENT_E);
+                known.insert(eg_res[1].0, SEGMENT_G);
             }
             Ordering::Greater => {
-                self.known_segments.insert(eg_res[1].0, SEGMENT_E);
-                self.known_segments.insert(eg_res[0].0, SEGMENT_G);
+                known.insert(eg_res[1].0, SEGMENT_E);
+                known.insert(eg_res[0].0, SEGMENT_G);
             }
             Ordering::Equal => {
-                panic!("malformed input");
+                return Err("segments e and g can't be told apart".to_string());
             }
         }
 
@@ -322,59 +212,168 @@ impl SegmentDisplay {
         // bd formed by isolating segments c/f, then xoring with 4 in order to get b/d
 
         let bd_res =
-            Self::count_chars_in_strings(&segments, &Self::xor_string(segments[9], &not_bd));
-        println!("{:?}", bd_res);
+            Self::count_chars_in_strings(segments, &Self::xor_string(segments[9], &not_bd));
+        if bd_res.len() != 2 {
+            return Err("segments b and d can't be found".to_string());
+        }
 
         match bd_res[0].1.cmp(&bd_res[1].1) {
             Ordering::Less => {
-                self.known_segments.insert(bd_res[0].0, SEGMENT_B);
-                self.known_segments.insert(bd_res[1].0, SEGMENT_D);
+                known.insert(bd_res[0].0, SEGMENT_B);
+                known.insert(bd_res[1].0, SEGMENT_D);
             }
             Ordering::Greater => {
-                self.known_segments.insert(bd_res[1].0, SEGMENT_B);
-                self.known_segments.insert(bd_res[0].0, SEGMENT_D);
+                known.insert(bd_res[1].0, SEGMENT_B);
+                known.insert(bd_res[0].0, SEGMENT_D);
             }
             Ordering::Equal => {
-                panic!("malformed input");
+                return Err("segments b and d can't be told apart".to_string());
+            }
+        }
+
+        self.known_segments = known;
+        Ok(())
+    }
+}
+
+const DIGITS: [u8; 10] = [ZERO, ONE, TWO, THREE, FOUR, FIVE, SIX, SEVEN, EIGHT, NINE];
+
+// wire index of a character, if it's one of a to g
+fn wire_index(c: char) -> Option<usize> {
+    match c {
+        'a'..='g' => Some(c as usize - 'a' as usize),
+        _ => None,
+    }
+}
+
+/// # Wiring
+///
+/// Which segment every wire (`a` to `g`, in order) is actually hooked
+/// up to, as one of the `SEGMENT_*` bits.
+///
+/// Unlike `SegmentDisplay::construct`, this doesn't rely on any tricks
+/// about which segments show up how often - it just tries wirings, and
+/// throws out anything that can't make a digit out of every pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Wiring([u8; 7]);
+
+impl Wiring {
+    fn display(&self, pattern: &str) -> Option<u8> {
+        pattern
+            .chars()
+            .try_fold(0, |acc, c| Some(acc | self.0[wire_index(c)?]))
+    }
+
+    fn decode(&self, pattern: &str) -> Option<usize> {
+        let display = self.display(pattern)?;
+        DIGITS.iter().position(|d| *d == display)
+    }
+
+    // the same shape as SegmentDisplay::known_segments
+    fn to_segment_map(self) -> HashMap<char, u8> {
+        ('a'..='g').zip(self.0.iter().copied()).collect()
+    }
+
+    /// The only wiring that fits every pattern, or every wiring that
+    /// does (none at all, if the patterns can't be digits).
+    fn solve(patterns: &[&str]) -> Result<Self, Vec<Self>> {
+        let mut found = Self::solve_all(patterns);
+
+        if found.len() == 1 {
+            Ok(found.pop().unwrap())
+        } else {
+            Err(found)
+        }
+    }
+
+    fn solve_all(patterns: &[&str]) -> Vec<Self> {
+        // patterns as wire bits rather than segment bits
+        let mut wires = Vec::new();
+        for p in patterns {
+            match p
+                .chars()
+                .try_fold(0u8, |acc, c| Some(acc | 1 << wire_index(c)?))
+            {
+                Some(w) => wires.push(w),
+                None => return Vec::new(),
             }
         }
-        // let cf_result = cf_split.map(|v|
 
-        /*
-        // insert eight, which has all seven segments
-        self.numbers.insert(8, segments[segments.len() - 1].into());
+        let mut found = Vec::new();
+        Self::search(&wires, &mut [None; 7], &mut found);
 
-        // insert 1, which has two
-        self.numbers.insert(1, segments[0].into());
+        found
+    }
 
-        // insert 7, which has three
-        self.numbers.insert(7, segments[1].into());
+    // narrows down what each wire could still be from what's already
+    // been picked, and gives up early if some pattern can't be a digit
+    // anymore. with every wire picked, this is a full check.
+    fn propagate(patterns: &[u8], assigned: &[Option<u8>; 7]) -> Option<[u8; 7]> {
+        let used = assigned.iter().flatten().fold(0, |acc, s| acc | s);
+        let mut candidates = [0u8; 7];
+        for (c, a) in candidates.iter_mut().zip(assigned.iter()) {
+            *c = a.unwrap_or(EIGHT & !used);
+        }
 
-        let mut one_seven_segs = segments[0].to_string();
-        one_seven_segs.push_str(segments[1]);
-        let seg_count = elem_count_in_vec(one_seven_segs.as_bytes());
+        for p in patterns {
+            let fits = |d: &&u8| {
+                d.count_ones() == p.count_ones()
+                    && assigned.iter().enumerate().all(|(w, a)| match a {
+                        Some(s) => (p & 1 << w != 0) == (*d & s != 0),
+                        None => true,
+                    })
+            };
+
+            let (mut inside, mut outside, mut any) = (0, 0, false);
+            for d in DIGITS.iter().filter(fits) {
+                inside |= d;
+                outside |= EIGHT & !d;
+                any = true;
+            }
 
-        self.segments.insert(*seg_count[0].0 as char, SEGMENT_A);
-        for c in seg_count[1..].iter() {
-            unknowns.push(*c.0 as char);
+            if !any {
+                return None;
+            }
+
+            for (w, c) in candidates.iter_mut().enumerate() {
+                *c &= if p & 1 << w != 0 { inside } else { outside };
+            }
         }
-        */
 
-        /*
-        let mut bd_segs = segments[2]
-            .as_bytes()
-            .iter()
-            .filter(|v| *v != seg_count[1].0 || *v != seg_count[2].0);
-            */
+        if candidates.contains(&0) {
+            return None;
+        }
+
+        Some(candidates)
+    }
+
+    fn search(patterns: &[u8], assigned: &mut [Option<u8>; 7], found: &mut Vec<Self>) {
+        let candidates = match Self::propagate(patterns, assigned) {
+            Some(c) => c,
+            None => return,
+        };
 
-        /*
-        segments = &mut segments[3..8];
-        let e_count = 0;
-        let g_count = 0;
-        for s in segments {}
-        */
+        // the wire with the least options left goes next
+        let next = (0..7)
+            .filter(|w| assigned[*w].is_none())
+            .min_by_key(|w| candidates[*w].count_ones());
 
-        // the rest of the segments
+        let w = match next {
+            Some(w) => w,
+            None => {
+                found.push(Wiring(candidates));
+                return;
+            }
+        };
+
+        for bit in 0..7 {
+            if candidates[w] & 1 << bit != 0 {
+                assigned[w] = Some(1 << bit);
+                Self::search(patterns, assigned, found);
+            }
+        }
+
+        assigned[w] = None;
     }
 }
 
@@ -395,6 +394,7 @@ fn elem_count_in_vec<'a, T: Ord + Clone>(vec: &'a [T]) -> Vec<(&'a T, usize)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::BitVec;
     use std::fs::File;
     use std::io::{BufRead, BufReader};
 
@@ -427,6 +427,98 @@ mod tests {
         println!("{}", res);
     }
 
+    #[test]
+    fn wiring_solver() {
+        let patterns = [
+            "acedgfb", "cdfbe", "gcdfa", "fbcad", "dab", "cefabd", "cdfgeb", "eafb", "cagedb", "ab",
+        ];
+
+        let wiring = Wiring::solve(&patterns).unwrap();
+        println!("{:?}", wiring);
+        assert_eq!(wiring.decode("cdfeb"), Some(5));
+        assert_eq!(wiring.decode("fcadb"), Some(3));
+        assert_eq!(wiring.decode("cdbaf"), Some(3));
+        assert_eq!(wiring.decode("acedgfb"), Some(8));
+        assert_eq!(wiring.decode("abc"), None);
+        assert_eq!(wiring.decode("xy"), None);
+
+        // just a one could be wired up in lots of ways
+        let candidates = Wiring::solve(&["ab"]).unwrap_err();
+        assert_eq!(candidates.len(), 2 * 120);
+        assert!(candidates.iter().all(|w| w.decode("ab") == Some(1)));
+
+        // nothing lights up one segment, or two different ones
+        assert!(Wiring::solve(&["a"]).unwrap_err().is_empty());
+        assert!(Wiring::solve(&["ab", "cd"]).unwrap_err().is_empty());
+        assert!(Wiring::solve(&["ax"]).unwrap_err().is_empty());
+    }
+
+    #[test]
+    fn construct_errors() {
+        // a fresh display every time, so nothing from an earlier
+        // attempt can hide anything
+        let fails = |patterns: &mut [&str]| {
+            let mut segment_map = SegmentDisplay::new();
+            let res = segment_map.try_construct(patterns);
+            assert!(segment_map.known_segments.is_empty());
+            res.is_err()
+        };
+
+        assert!(fails(&mut ["ab", "abc"]));
+        assert!(fails(&mut ["ab"; 10]));
+
+        // right lengths, but a and b light up in every pattern
+        assert!(fails(&mut [
+            "ab", "abc", "abcd", "abcde", "abcde", "abcde", "abcdef", "abcdef", "abcdef",
+            "abcdefg",
+        ]));
+
+        // right amount of bytes, but not of wires
+        assert!(fails(&mut [
+            "é", "abc", "abcd", "abcde", "abcde", "abcde", "abcdef", "abcdef", "abcdef", "abcdefg",
+        ]));
+        assert!(fails(&mut [
+            "aa", "abc", "abcd", "abcde", "abcde", "abcde", "abcdef", "abcdef", "abcdef",
+            "abcdefg",
+        ]));
+        assert!(fails(&mut [
+            "ax", "abc", "abcd", "abcde", "abcde", "abcde", "abcdef", "abcdef", "abcdef",
+            "abcdefg",
+        ]));
+    }
+
+    // the heuristic decoder has to agree with the solver everywhere
+    #[test]
+    fn cross_validate_decoders() {
+        for path in ["res/day_eight.test", "res/day_eight.input"].iter() {
+            let file = File::open(path).unwrap();
+
+            for l in BufReader::new(file).lines() {
+                let line = l.unwrap();
+                let mut parts = line
+                    .split('|')
+                    .map(|s| s.split_whitespace().collect::<Vec<&str>>())
+                    .collect::<Vec<Vec<&str>>>();
+
+                let all = parts.concat();
+                let wiring = Wiring::solve(&all).unwrap();
+
+                let mut segment_map = SegmentDisplay::new();
+                segment_map.try_construct(&mut parts[0]).unwrap();
+                assert_eq!(
+                    segment_map.known_segments,
+                    wiring.to_segment_map(),
+                    "{}",
+                    line
+                );
+
+                for p in parts[1].iter() {
+                    assert_eq!(Some(segment_map.string_to_number(p)), wiring.decode(p));
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_str_sort() {
         let mut test_input = [
@@ -466,7 +558,11 @@ mod tests {
 
     #[test]
     fn u8_splitting() {
-        let res = BitIter::new(1).enumerate().collect::<Vec<(usize, u8)>>();
+        let res = BitVec::from_word(1u8, 8)
+            .iter()
+            .rev()
+            .enumerate()
+            .collect::<Vec<(usize, bool)>>();
 
         println!("{:?}", res);
     }
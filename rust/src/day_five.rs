@@ -1,5 +1,4 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::hash::Hash;
 use std::io::{BufRead, BufReader};
@@ -8,23 +7,6 @@ use std::io::{BufRead, BufReader};
 struct Coordinates(pub isize, pub isize);
 
 impl Coordinates {
-    // move towards another set of coordinates at some
-    // acceleration
-    fn move_towards(&mut self, other: &Self, acc: usize) {
-        let direction = other.get_direction(self);
-        self.0 = match direction.0 {
-            Ordering::Greater => self.0 + acc as isize,
-            Ordering::Less => self.0 - acc as isize,
-            Ordering::Equal => self.0,
-        };
-
-        self.1 = match direction.1 {
-            Ordering::Greater => self.1 + acc as isize,
-            Ordering::Less => self.1 - acc as isize,
-            Ordering::Equal => self.1,
-        };
-    }
-
     fn delta_between(&self, other: &Self) -> (isize, isize) {
         ((other.0 - self.0).abs(), (other.1 - self.1).abs())
     }
@@ -39,29 +21,22 @@ impl Coordinates {
     fn is_level(&self, other: &Self) -> bool {
         !self.is_diagonal(other)
     }
-
-    // get the direction from self to other (in terms of order/order)
-    fn get_direction(&self, other: &Self) -> (Ordering, Ordering) {
-        (self.0.cmp(&other.0), self.1.cmp(&other.1))
-    }
 }
 
 #[derive(Debug, Hash)]
 struct Vector {
     head: Coordinates,
     tail: Coordinates,
-    pos: Option<Coordinates>, // for iterating from head->tail
-    acc: usize,
+    acc: usize, // stride, in raster steps
 }
 
 impl Vector {
     fn new(head: Coordinates, tail: Coordinates, acc: usize) -> Self {
-        Vector {
-            head,
-            tail,
-            pos: None,
-            acc,
+        if acc == 0 {
+            panic!("vector stride must be non-zero");
         }
+
+        Vector { head, tail, acc }
     }
 
     fn is_level(&self) -> bool {
@@ -89,32 +64,109 @@ impl Vector {
 
         Self::new(coord_set[0].clone(), coord_set[1].clone(), 1)
     }
-}
 
-impl Iterator for Vector {
-    type Item = Coordinates;
+    // the amount of raster steps between tail and head, and the
+    // offset the i-th step is at from the tail
+    fn steps(&self, raster: Raster) -> usize {
+        let (dx, dy) = self.tail.delta_between(&self.head);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.pos.is_none() {
-            self.pos = Some(self.tail.clone());
-            return self.pos.clone();
+        match raster {
+            Raster::Bresenham => dx.max(dy) as usize,
+            Raster::Lattice => gcd(dx, dy) as usize,
         }
+    }
 
-        if self.pos.as_ref().unwrap().eq(&self.head) {
-            self.pos = None;
-            return None;
+    fn point_at(&self, raster: Raster, n: usize, i: usize) -> Coordinates {
+        let dx = self.head.0 - self.tail.0;
+        let dy = self.head.1 - self.tail.1;
+
+        if n == 0 {
+            return self.tail.clone();
         }
 
-        if self.head.is_diagonal(&self.tail) && !self.is_45deg() {
-            return None;
+        let (n, i) = (n as isize, i as isize);
+        let offset = |d: isize| match raster {
+            // round i * d / n to the nearest integer (halves go up),
+            // which is the midpoint rule bresenham uses
+            Raster::Bresenham => (2 * i * d + n).div_euclid(2 * n),
+            Raster::Lattice => i * d / n,
+        };
+
+        Coordinates(self.tail.0 + offset(dx), self.tail.1 + offset(dy))
+    }
+
+    /// Every point on the vector starting from the given end, `acc`
+    /// raster steps apart. The far end is always the last point, even
+    /// if the stride doesn't divide the line evenly.
+    fn points(&self, from: End, raster: Raster) -> VectorPoints<'_> {
+        VectorPoints {
+            vector: self,
+            raster,
+            from,
+            n: self.steps(raster),
+            i: Some(0),
         }
+    }
+}
+
+fn gcd(a: isize, b: isize) -> isize {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// # Raster
+///
+/// Which points count as being on a line. `Bresenham` gives one point
+/// per step along the longer axis, so every line is connected.
+/// `Lattice` only gives the points the line passes through exactly.
+/// For level and 45 degree lines both are the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Raster {
+    Bresenham,
+    Lattice,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum End {
+    Head,
+    Tail,
+}
+
+/// # VectorPoints
+///
+/// Iterator over the points of a vector. Points are always worked out
+/// from the tail, so going from either end gives the same set.
+#[derive(Debug)]
+struct VectorPoints<'a> {
+    vector: &'a Vector,
+    raster: Raster,
+    from: End,
+    n: usize,
+    // steps taken so far, None once the far end has been given out
+    i: Option<usize>,
+}
+
+impl Iterator for VectorPoints<'_> {
+    type Item = Coordinates;
 
-        self.pos
-            .as_mut()
-            .unwrap()
-            .move_towards(&self.head, self.acc);
+    fn next(&mut self) -> Option<Self::Item> {
+        let i = self.i?;
+
+        self.i = if i == self.n {
+            None
+        } else {
+            Some((i + self.vector.acc).min(self.n))
+        };
+
+        let step = match self.from {
+            End::Tail => i,
+            End::Head => self.n - i,
+        };
 
-        self.pos.clone()
+        Some(self.vector.point_at(self.raster, self.n, step))
     }
 }
 
@@ -122,6 +174,7 @@ impl Iterator for Vector {
 struct VectorMap {
     pub map: HashMap<Coordinates, usize>,
     vectors: Vec<Vector>,
+    raster: Raster,
 }
 
 impl VectorMap {
@@ -129,6 +182,7 @@ impl VectorMap {
         VectorMap {
             map: HashMap::new(),
             vectors: Vec::new(),
+            raster: Raster::Bresenham,
         }
     }
 
@@ -137,11 +191,7 @@ impl VectorMap {
         let mut vmap = VectorMap::new();
 
         for l in lines.flatten() {
-            let v = Vector::from_string(&l);
-
-            if v.is_level() || v.is_45deg() {
-                vmap.add_vector(v);
-            }
+            vmap.add_vector(Vector::from_string(&l));
         }
 
         vmap
@@ -151,10 +201,16 @@ impl VectorMap {
         self.vectors.push(vec);
     }
 
+    // drops every vector the filter doesn't keep - the map needs
+    // populating again after this
+    fn retain(&mut self, filter: VectorFilter) {
+        self.vectors.retain(|v| filter.keeps(v));
+    }
+
     fn populate_map(&mut self) {
         self.map.clear();
-        for v in self.vectors.iter_mut() {
-            for c in v {
+        for v in self.vectors.iter() {
+            for c in v.points(End::Tail, self.raster) {
                 if let Some(m) = self.map.get(&c) {
                     let n = m + 1;
                     self.map.insert(c, n);
@@ -166,6 +222,430 @@ impl VectorMap {
     }
 }
 
+/// # LineFamily
+///
+/// The four directions a line can go in and still be counted exactly.
+/// Every line in a family is identified by one number (`line`), and
+/// every point on it by another (`t`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum LineFamily {
+    // y is fixed, t is x
+    Horizontal,
+    // x is fixed, t is y
+    Vertical,
+    // x - y is fixed, t is x
+    Diagonal,
+    // x + y is fixed, t is x
+    AntiDiagonal,
+}
+
+impl LineFamily {
+    fn of(v: &Vector) -> Option<Self> {
+        let (dx, dy) = (v.head.0 - v.tail.0, v.head.1 - v.tail.1);
+
+        match (dx, dy) {
+            (_, 0) => Some(LineFamily::Horizontal),
+            (0, _) => Some(LineFamily::Vertical),
+            _ if dx == dy => Some(LineFamily::Diagonal),
+            _ if dx == -dy => Some(LineFamily::AntiDiagonal),
+            _ => None,
+        }
+    }
+
+    // (line, t) for some point
+    fn locate(&self, c: &Coordinates) -> (isize, isize) {
+        match self {
+            LineFamily::Horizontal => (c.1, c.0),
+            LineFamily::Vertical => (c.0, c.1),
+            LineFamily::Diagonal => (c.0 - c.1, c.0),
+            LineFamily::AntiDiagonal => (c.0 + c.1, c.0),
+        }
+    }
+}
+
+/// # CoverageRun
+///
+/// A stretch of some line that's covered by the same amount of
+/// vectors from one family, from `lo` to `hi` inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CoverageRun {
+    family: LineFamily,
+    line: isize,
+    lo: isize,
+    hi: isize,
+    count: usize,
+}
+
+impl CoverageRun {
+    fn len(&self) -> usize {
+        (self.hi - self.lo + 1) as usize
+    }
+
+    // the point at some t along the run's line
+    fn point(&self, t: isize) -> Coordinates {
+        match self.family {
+            LineFamily::Horizontal => Coordinates(t, self.line),
+            LineFamily::Vertical => Coordinates(self.line, t),
+            LineFamily::Diagonal => Coordinates(t, t - self.line),
+            LineFamily::AntiDiagonal => Coordinates(t, self.line - t),
+        }
+    }
+
+    fn contains(&self, c: &Coordinates) -> bool {
+        let (line, t) = self.family.locate(c);
+
+        line == self.line && self.lo <= t && t <= self.hi
+    }
+
+    // where two runs from different families cross, if they do on a
+    // lattice point
+    fn intersection(&self, other: &Self) -> Option<Coordinates> {
+        use LineFamily::*;
+
+        let (a, b) = (self.line, other.line);
+        let c = match (self.family, other.family) {
+            (Horizontal, Vertical) => Coordinates(b, a),
+            (Horizontal, Diagonal) => Coordinates(b + a, a),
+            (Horizontal, AntiDiagonal) => Coordinates(b - a, a),
+            (Vertical, Diagonal) => Coordinates(a, a - b),
+            (Vertical, AntiDiagonal) => Coordinates(a, b - a),
+            (Diagonal, AntiDiagonal) if (a + b) % 2 == 0 => Coordinates((a + b) / 2, (b - a) / 2),
+            (Diagonal, AntiDiagonal) => return None,
+            (f, g) if f == g => return None,
+            _ => return other.intersection(self),
+        };
+
+        if self.contains(&c) && other.contains(&c) {
+            Some(c)
+        } else {
+            None
+        }
+    }
+}
+
+// sweeps along each line, turning overlapping vectors into
+// runs of constant coverage
+fn coverage_runs(vectors: &[Vector]) -> Result<Vec<CoverageRun>, String> {
+    let mut events: HashMap<(LineFamily, isize), Vec<(isize, isize)>> = HashMap::new();
+
+    for v in vectors {
+        let family = LineFamily::of(v).ok_or(format!("unsupported vector: {:?}", v))?;
+        let (line, a) = family.locate(&v.head);
+        let (_, b) = family.locate(&v.tail);
+
+        let e = events.entry((family, line)).or_default();
+        e.push((a.min(b), 1));
+        e.push((a.max(b) + 1, -1));
+    }
+
+    let mut runs = Vec::new();
+    for ((family, line), mut e) in events {
+        e.sort_unstable();
+
+        let mut count: isize = 0;
+        for w in 0..e.len() {
+            count += e[w].1;
+
+            if let Some(next) = e.get(w + 1) {
+                if count > 0 && next.0 > e[w].0 {
+                    runs.push(CoverageRun {
+                        family,
+                        line,
+                        lo: e[w].0,
+                        hi: next.0 - 1,
+                        count: count as usize,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(runs)
+}
+
+impl VectorMap {
+    /// Counts the points covered by at least `k` vectors without walking
+    /// any of them. Only works with level and 45 degree vectors, and
+    /// `k` of at least 1.
+    ///
+    /// Points only one family covers are just the lengths of the runs.
+    /// Points where families cross get counted once per run by that,
+    /// so those get corrected afterwards.
+    ///
+    /// To find the crossings, runs are bucketed by family and line. A run
+    /// only gets checked against runs on the lines of other families
+    /// that it actually passes over, rather than against every other
+    /// run. That's still quadratic in the worst case (lots of long runs
+    /// all crossing the same lines), but fine for puzzle-like input.
+    fn count_overlaps(&self, k: usize) -> Result<usize, String> {
+        // every point in the plane is covered by at least nothing
+        if k == 0 {
+            return Err("k has to be at least 1".to_string());
+        }
+
+        let runs = coverage_runs(&self.vectors)?;
+        let mut total = runs
+            .iter()
+            .filter(|r| r.count >= k)
+            .map(|r| r.len())
+            .sum::<usize>();
+
+        let families = [
+            LineFamily::Horizontal,
+            LineFamily::Vertical,
+            LineFamily::Diagonal,
+            LineFamily::AntiDiagonal,
+        ];
+
+        let mut by_line: Vec<BTreeMap<isize, Vec<&CoverageRun>>> = vec![BTreeMap::new(); 4];
+        for r in runs.iter() {
+            by_line[r.family as usize]
+                .entry(r.line)
+                .or_default()
+                .push(r);
+        }
+
+        let mut crossings: HashMap<Coordinates, [usize; 4]> = HashMap::new();
+        for r in runs.iter() {
+            // each pair of families only needs checking one way round
+            for g in families.iter().filter(|g| **g as usize > r.family as usize) {
+                let (a, _) = g.locate(&r.point(r.lo));
+                let (b, _) = g.locate(&r.point(r.hi));
+
+                for o in by_line[*g as usize]
+                    .range(a.min(b)..=a.max(b))
+                    .flat_map(|l| l.1)
+                {
+                    if let Some(c) = r.intersection(o) {
+                        let counts = crossings.entry(c).or_default();
+                        counts[r.family as usize] = r.count;
+                        counts[o.family as usize] = o.count;
+                    }
+                }
+            }
+        }
+
+        for counts in crossings.values() {
+            let counted = counts.iter().filter(|c| **c >= k).count();
+            let covered = (counts.iter().sum::<usize>() >= k) as usize;
+
+            total = total + covered - counted;
+        }
+
+        Ok(total)
+    }
+}
+
+/// # VectorSummary
+///
+/// How much of one vector is covered by other vectors as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VectorSummary {
+    vector: usize,
+    points: usize,
+    shared: usize,
+}
+
+/// # RegionCoverage
+///
+/// Coverage of every point inside some rectangle. `hits` is the sum
+/// of the coverage of all points, `max` the highest of them.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct RegionCoverage {
+    area: usize,
+    covered: usize,
+    overlapping: usize,
+    hits: usize,
+    max: usize,
+}
+
+// all of these read from the map, so populate_map has to be called first
+impl VectorMap {
+    fn coverage_at(&self, c: &Coordinates) -> usize {
+        self.map.get(c).copied().unwrap_or(0)
+    }
+
+    /// Every point at least `k` vectors go through, ordered by row and
+    /// then by column.
+    fn points_at_least(&self, k: usize) -> Vec<(Coordinates, usize)> {
+        let mut points = self
+            .map
+            .iter()
+            .filter(|(_, n)| **n >= k)
+            .map(|(c, n)| (c.clone(), *n))
+            .collect::<Vec<_>>();
+
+        points.sort_by_key(|(c, _)| (c.1, c.0));
+        points
+    }
+
+    /// Coverage inside the rectangle with corners `a` and `b`, both
+    /// corners included.
+    fn coverage_in(&self, a: &Coordinates, b: &Coordinates) -> RegionCoverage {
+        let (x0, x1) = (a.0.min(b.0), a.0.max(b.0));
+        let (y0, y1) = (a.1.min(b.1), a.1.max(b.1));
+
+        let mut region = RegionCoverage {
+            area: ((x1 - x0 + 1) * (y1 - y0 + 1)) as usize,
+            ..RegionCoverage::default()
+        };
+
+        for (c, n) in self.map.iter() {
+            if c.0 < x0 || c.0 > x1 || c.1 < y0 || c.1 > y1 {
+                continue;
+            }
+
+            region.covered += 1;
+            region.overlapping += (*n > 1) as usize;
+            region.hits += n;
+            region.max = region.max.max(*n);
+        }
+
+        region
+    }
+
+    /// Indices of every vector that goes through `c`.
+    fn vectors_through(&self, c: &Coordinates) -> Vec<usize> {
+        self.vectors
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| {
+                let (x0, x1) = (v.head.0.min(v.tail.0), v.head.0.max(v.tail.0));
+                let (y0, y1) = (v.head.1.min(v.tail.1), v.head.1.max(v.tail.1));
+
+                (x0..=x1).contains(&c.0)
+                    && (y0..=y1).contains(&c.1)
+                    && v.points(End::Tail, self.raster).any(|p| p == *c)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// For every vector, how many of its points some other vector
+    /// goes through as well.
+    fn summarize_vectors(&self) -> Vec<VectorSummary> {
+        self.vectors
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let mut summary = VectorSummary {
+                    vector: i,
+                    points: 0,
+                    shared: 0,
+                };
+
+                for p in v.points(End::Tail, self.raster) {
+                    summary.points += 1;
+                    summary.shared += (self.coverage_at(&p) > 1) as usize;
+                }
+
+                summary
+            })
+            .collect()
+    }
+}
+
+/// # VectorFilter
+///
+/// Which vectors to keep, either when rendering a map or when
+/// trimming it down to what the puzzle asks about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VectorFilter {
+    All,
+    Level,
+    Diagonal,
+    // level or 45 degrees, i.e. what part two counts
+    Straight,
+}
+
+impl VectorFilter {
+    fn keeps(&self, v: &Vector) -> bool {
+        match self {
+            VectorFilter::All => true,
+            VectorFilter::Level => v.is_level(),
+            VectorFilter::Diagonal => v.is_45deg(),
+            VectorFilter::Straight => v.is_level() || v.is_45deg(),
+        }
+    }
+}
+
+impl VectorMap {
+    // like populate_map, but only for some vectors and without touching
+    // the map itself
+    fn filtered_coverage(&self, filter: VectorFilter) -> HashMap<Coordinates, usize> {
+        let mut coverage = HashMap::new();
+
+        for v in self.vectors.iter().filter(|v| filter.keeps(v)) {
+            for c in v.points(End::Tail, self.raster) {
+                *coverage.entry(c).or_insert(0) += 1;
+            }
+        }
+
+        coverage
+    }
+
+    // (min, max) corners of everything covered
+    fn bounds(coverage: &HashMap<Coordinates, usize>) -> Option<(Coordinates, Coordinates)> {
+        let min_x = coverage.keys().map(|c| c.0).min()?;
+        let max_x = coverage.keys().map(|c| c.0).max()?;
+        let min_y = coverage.keys().map(|c| c.1).min()?;
+        let max_y = coverage.keys().map(|c| c.1).max()?;
+
+        Some((Coordinates(min_x, min_y), Coordinates(max_x, max_y)))
+    }
+
+    /// The diagram from the puzzle: a dot where nothing goes through,
+    /// otherwise the amount of vectors (or `+` for more than nine).
+    /// Covers the bounding box of the drawn vectors, one line per row.
+    fn render(&self, filter: VectorFilter) -> String {
+        let coverage = self.filtered_coverage(filter);
+        let (min, max) = match Self::bounds(&coverage) {
+            Some(b) => b,
+            None => return String::new(),
+        };
+
+        let mut diagram = String::new();
+        for y in min.1..=max.1 {
+            for x in min.0..=max.0 {
+                diagram.push(match coverage.get(&Coordinates(x, y)) {
+                    None => '.',
+                    Some(n) if *n > 9 => '+',
+                    Some(n) => std::char::from_digit(*n as u32, 10).unwrap(),
+                });
+            }
+            diagram.push('\n');
+        }
+
+        diagram
+    }
+
+    /// The same thing as `render`, but as a plain (P2) greyscale PGM
+    /// image. Black is nothing, white is the most covered point.
+    fn to_pgm(&self, filter: VectorFilter) -> String {
+        let coverage = self.filtered_coverage(filter);
+        let (min, max) =
+            Self::bounds(&coverage).unwrap_or((Coordinates(0, 0), Coordinates(-1, -1)));
+        let peak = coverage.values().max().copied().unwrap_or(1);
+
+        let mut image = format!("P2\n{} {}\n255\n", max.0 - min.0 + 1, max.1 - min.1 + 1);
+
+        for y in min.1..=max.1 {
+            let row = (min.0..=max.0)
+                .map(|x| {
+                    let n = coverage.get(&Coordinates(x, y)).copied().unwrap_or(0);
+                    (n * 255 / peak).to_string()
+                })
+                .collect::<Vec<String>>()
+                .join(" ");
+
+            image.push_str(&row);
+            image.push('\n');
+        }
+
+        image
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,7 +655,7 @@ mod tests {
     fn vector_45deg_test() {
         let vec = Vector::from_string("0,0 -> 5,5");
         println!("{}", vec.is_45deg());
-        for c in vec {
+        for c in vec.points(End::Tail, Raster::Bresenham) {
             println!("{:?}", c);
         }
 
@@ -188,11 +668,7 @@ mod tests {
         let file = File::open("res/day_five.input").unwrap();
         let mut vmap_p1 = VectorMap::from_file(file);
 
-        vmap_p1.vectors = vmap_p1
-            .vectors
-            .into_iter()
-            .filter(|v| v.is_level())
-            .collect();
+        vmap_p1.retain(VectorFilter::Level);
         vmap_p1.populate_map();
 
         let count = vmap_p1.map.iter().filter(|v| v.1 > &1).count();
@@ -202,6 +678,7 @@ mod tests {
         let file = File::open("res/day_five.input").unwrap();
         let mut vmap_p2 = VectorMap::from_file(file);
 
+        vmap_p2.retain(VectorFilter::Straight);
         vmap_p2.populate_map();
 
         let count = vmap_p2.map.iter().filter(|v| v.1 > &1).count();
@@ -214,6 +691,7 @@ mod tests {
         let file = File::open("res/day_five.test").unwrap();
         let mut vmap = VectorMap::from_file(file);
 
+        vmap.retain(VectorFilter::Straight);
         vmap.populate_map();
 
         println!("{:?}", vmap);
@@ -261,15 +739,293 @@ mod tests {
 
     #[test]
     fn vector_move() {
-        let mut test_vec = test_vector();
+        let test_vec = test_vector();
         let test_vec_ew = test_vector_ew();
 
-        for c in test_vec {
+        for c in test_vec.points(End::Tail, Raster::Bresenham) {
             println!("{:?}", c);
         }
 
-        for c in test_vec_ew {
+        for c in test_vec_ew.points(End::Tail, Raster::Bresenham) {
             println!("{:?}", c);
         }
     }
+
+    fn collect_points(v: &Vector, from: End, raster: Raster) -> Vec<(isize, isize)> {
+        v.points(from, raster).map(|c| (c.0, c.1)).collect()
+    }
+
+    #[test]
+    fn vector_any_slope() {
+        // the parsed line goes from head to tail
+        let vec = Vector::from_string("0,0 -> 5,2");
+
+        assert_eq!(
+            collect_points(&vec, End::Head, Raster::Bresenham),
+            vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]
+        );
+        // 0,0 -> 5,2 doesn't go through any other whole point
+        assert_eq!(
+            collect_points(&vec, End::Head, Raster::Lattice),
+            vec![(0, 0), (5, 2)]
+        );
+
+        let vec = Vector::from_string("6,4 -> 0,0");
+        assert_eq!(
+            collect_points(&vec, End::Tail, Raster::Lattice),
+            vec![(0, 0), (3, 2), (6, 4)]
+        );
+
+        // steep and backwards lines still hit every row once
+        let vec = Vector::from_string("3,-4 -> 1,3");
+        let points = collect_points(&vec, End::Head, Raster::Bresenham);
+        println!("{:?}", points);
+        assert_eq!(points.len(), 8);
+        assert_eq!(points.first(), Some(&(3, -4)));
+        assert_eq!(points.last(), Some(&(1, 3)));
+
+        let mut reversed = collect_points(&vec, End::Tail, Raster::Bresenham);
+        reversed.reverse();
+        assert_eq!(points, reversed);
+
+        let vec = Vector::from_string("2,2 -> 2,2");
+        assert_eq!(
+            collect_points(&vec, End::Tail, Raster::Bresenham),
+            vec![(2, 2)]
+        );
+    }
+
+    #[test]
+    fn vector_stride() {
+        let vec = Vector::new(Coordinates(0, 5), Coordinates(0, 0), 2);
+
+        assert_eq!(
+            collect_points(&vec, End::Tail, Raster::Bresenham),
+            vec![(0, 0), (0, 2), (0, 4), (0, 5)]
+        );
+        assert_eq!(
+            collect_points(&vec, End::Head, Raster::Bresenham),
+            vec![(0, 5), (0, 3), (0, 1), (0, 0)]
+        );
+
+        let vec = Vector::new(Coordinates(9, 9), Coordinates(0, 0), 3);
+        assert_eq!(
+            collect_points(&vec, End::Tail, Raster::Bresenham),
+            vec![(0, 0), (3, 3), (6, 6), (9, 9)]
+        );
+
+        // a stride longer than the line just gives both ends
+        let vec = Vector::new(Coordinates(2, 1), Coordinates(0, 0), 10);
+        assert_eq!(
+            collect_points(&vec, End::Tail, Raster::Bresenham),
+            vec![(0, 0), (2, 1)]
+        );
+    }
+
+    #[test]
+    fn example_overlaps_with_rasters() {
+        for raster in [Raster::Bresenham, Raster::Lattice] {
+            let file = File::open("res/day_five.test").unwrap();
+            let mut vmap = VectorMap::from_file(file);
+            vmap.retain(VectorFilter::Straight);
+            vmap.raster = raster;
+            vmap.populate_map();
+
+            assert_eq!(vmap.map.values().filter(|c| **c > 1).count(), 12);
+        }
+    }
+
+    #[test]
+    fn vmap_queries() {
+        let file = File::open("res/day_five.test").unwrap();
+        let mut vmap = VectorMap::from_file(file);
+        vmap.retain(VectorFilter::Straight);
+        vmap.populate_map();
+
+        assert_eq!(vmap.coverage_at(&Coordinates(7, 4)), 2);
+        assert_eq!(vmap.coverage_at(&Coordinates(4, 4)), 3);
+        assert_eq!(vmap.coverage_at(&Coordinates(9, 9)), 0);
+
+        let overlapping = vmap.points_at_least(2);
+        assert_eq!(overlapping.len(), 12);
+        assert_eq!(overlapping[0], (Coordinates(7, 1), 2));
+        assert_eq!(
+            vmap.points_at_least(3),
+            vec![(Coordinates(4, 4), 3), (Coordinates(6, 4), 3)]
+        );
+
+        let region = vmap.coverage_in(&Coordinates(5, 5), &Coordinates(3, 3));
+        println!("{:?}", region);
+        assert_eq!(region.area, 9);
+        assert_eq!(region.max, 3);
+        assert_eq!(
+            region.covered,
+            vmap.coverage_in(&Coordinates(3, 3), &Coordinates(5, 5))
+                .covered
+        );
+
+        let through = vmap.vectors_through(&Coordinates(4, 4));
+        assert_eq!(through.len(), 3);
+        assert!(through
+            .iter()
+            .all(|i| vmap.summarize_vectors()[*i].shared > 0));
+
+        let summary = vmap.summarize_vectors();
+        for s in summary.iter() {
+            println!("{:?}", s);
+            assert!(s.shared <= s.points);
+        }
+        assert_eq!(
+            summary.iter().map(|s| s.points).sum::<usize>(),
+            vmap.map.values().sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn vmap_render() {
+        let file = File::open("res/day_five.test").unwrap();
+        let mut vmap = VectorMap::from_file(file);
+        vmap.retain(VectorFilter::Straight);
+
+        let diagram = vmap.render(VectorFilter::All);
+        println!("{}", diagram);
+        assert_eq!(
+            diagram,
+            "1.1....11.\n\
+             .111...2..\n\
+             ..2.1.111.\n\
+             ...1.2.2..\n\
+             .112313211\n\
+             ...1.2....\n\
+             ..1...1...\n\
+             .1.....1..\n\
+             1.......1.\n\
+             222111....\n"
+        );
+
+        let diagram = vmap.render(VectorFilter::Level);
+        println!("{}", diagram);
+        assert_eq!(
+            diagram,
+            ".......1..\n\
+             ..1....1..\n\
+             ..1....1..\n\
+             .......1..\n\
+             .112111211\n\
+             ..........\n\
+             ..........\n\
+             ..........\n\
+             ..........\n\
+             222111....\n"
+        );
+
+        let diagram = vmap.render(VectorFilter::Diagonal);
+        assert_eq!(diagram.lines().count(), 9);
+        assert!(diagram.lines().all(|l| l.len() == 9));
+
+        let image = vmap.to_pgm(VectorFilter::All);
+        let mut lines = image.lines();
+        assert_eq!(lines.next(), Some("P2"));
+        assert_eq!(lines.next(), Some("10 10"));
+        assert_eq!(lines.next(), Some("255"));
+        assert_eq!(lines.next(), Some("85 0 85 0 0 0 0 85 85 0"));
+        assert_eq!(lines.nth(3), Some("0 85 85 170 255 85 255 170 85 85"));
+
+        assert_eq!(VectorMap::new().render(VectorFilter::All), "");
+        assert_eq!(VectorMap::new().to_pgm(VectorFilter::All), "P2\n0 0\n255\n");
+    }
+
+    #[test]
+    fn coverage_run_sweep() {
+        let mut vmap = VectorMap::new();
+        vmap.add_vector(Vector::from_string("0,0 -> 5,0"));
+        vmap.add_vector(Vector::from_string("7,0 -> 3,0"));
+
+        let mut runs = coverage_runs(&vmap.vectors).unwrap();
+        runs.sort_by_key(|r| r.lo);
+
+        assert_eq!(
+            runs.iter()
+                .map(|r| (r.lo, r.hi, r.count))
+                .collect::<Vec<(isize, isize, usize)>>(),
+            vec![(0, 2, 1), (3, 5, 2), (6, 7, 1)]
+        );
+
+        // every family can go from t back to a point on its line
+        let file = File::open("res/day_five.test").unwrap();
+        let mut vmap = VectorMap::from_file(file);
+        vmap.retain(VectorFilter::Straight);
+        for r in coverage_runs(&vmap.vectors).unwrap() {
+            for t in r.lo..=r.hi {
+                assert!(r.contains(&r.point(t)));
+                assert_eq!(r.family.locate(&r.point(t)), (r.line, t));
+            }
+        }
+    }
+
+    #[test]
+    fn analytic_overlaps() {
+        let file = File::open("res/day_five.test").unwrap();
+        let mut vmap = VectorMap::from_file(file);
+        vmap.retain(VectorFilter::Straight);
+
+        assert_eq!(vmap.count_overlaps(2), Ok(12));
+
+        vmap.populate_map();
+        for k in 1..4 {
+            assert_eq!(
+                vmap.count_overlaps(k),
+                Ok(vmap.map.values().filter(|v| **v >= k).count())
+            );
+        }
+
+        let file = File::open("res/day_five.input").unwrap();
+        let mut vmap = VectorMap::from_file(file);
+
+        vmap.retain(VectorFilter::Straight);
+        vmap.populate_map();
+        for k in 1..4 {
+            assert_eq!(
+                vmap.count_overlaps(k),
+                Ok(vmap.map.values().filter(|v| **v >= k).count())
+            );
+        }
+    }
+
+    #[test]
+    fn analytic_overlaps_large() {
+        let mut vmap = VectorMap::new();
+        vmap.add_vector(Vector::from_string("0,0 -> 4000000,4000000"));
+        vmap.add_vector(Vector::from_string("0,4000000 -> 4000000,0"));
+        vmap.add_vector(Vector::from_string("0,2000000 -> 4000000,2000000"));
+        vmap.add_vector(Vector::from_string("1000000,1000000 -> 3000000,3000000"));
+        vmap.add_vector(Vector::from_string("0,1 -> 0,1"));
+
+        // the centre has all four, the other overlap is the doubled diagonal
+        assert_eq!(vmap.count_overlaps(2), Ok(2000001));
+        assert_eq!(vmap.count_overlaps(4), Ok(1));
+        assert_eq!(vmap.count_overlaps(1), Ok(3 * 4000001 - 2 + 1));
+
+        let mut odd = VectorMap::new();
+        odd.add_vector(Vector::from_string("0,0 -> 1,1"));
+        odd.add_vector(Vector::from_string("0,1 -> 1,0"));
+        assert_eq!(odd.count_overlaps(2), Ok(0));
+
+        odd.add_vector(Vector::from_string("0,0 -> 2,1"));
+        assert!(odd.count_overlaps(2).is_err());
+    }
+
+    #[test]
+    fn analytic_overlaps_crossing() {
+        let mut vmap = VectorMap::new();
+        vmap.add_vector(Vector::from_string("0,0 -> 1,0"));
+        vmap.add_vector(Vector::from_string("0,0 -> 0,1"));
+
+        assert_eq!(vmap.count_overlaps(1), Ok(3));
+        assert_eq!(vmap.count_overlaps(2), Ok(1));
+        assert_eq!(vmap.count_overlaps(3), Ok(0));
+
+        // that would be the whole plane
+        assert!(vmap.count_overlaps(0).is_err());
+    }
 }
@@ -1,8 +1,9 @@
+use crate::util::Rng;
 use std::cmp::Ordering;
-use std::collections::{BTreeSet, HashMap};
-use std::convert::TryInto;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Write};
+use std::rc::Rc;
 
 #[derive(Debug)]
 struct Bingo {
@@ -10,6 +11,40 @@ struct Bingo {
     tables: Vec<BingoTable>,
 }
 
+/// # BingoWin
+///
+/// A table winning: which draw did it, the pattern it won with,
+/// and the sum of whatever was left unmarked at the time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BingoWin {
+    table: usize,
+    pattern: WinPattern,
+    draw_index: usize,
+    draw: usize,
+    unmarked: usize,
+    score: usize,
+}
+
+/// # BingoTimeline
+///
+/// Every win over a whole game, in the order they happened, and
+/// the ids of any tables that never won.
+#[derive(Debug, Default)]
+struct BingoTimeline {
+    wins: Vec<BingoWin>,
+    never_won: Vec<usize>,
+}
+
+impl BingoTimeline {
+    fn first(&self) -> Option<&BingoWin> {
+        self.wins.first()
+    }
+
+    fn last(&self) -> Option<&BingoWin> {
+        self.wins.last()
+    }
+}
+
 impl Bingo {
     fn new() -> Self {
         Bingo {
@@ -18,153 +53,297 @@ impl Bingo {
         }
     }
 
-    fn parse_from_file(file: File) -> Self {
-        let mut lines = BufReader::new(file).lines().peekable();
+    // sets which patterns count as a win on every table
+    fn set_patterns(&mut self, patterns: &[WinPattern]) {
+        let mut masks: HashMap<(usize, usize), Rc<WinMasks>> = HashMap::new();
+
+        for t in &mut self.tables {
+            let m = masks
+                .entry((t.rows, t.cols))
+                .or_insert_with(|| Rc::new(WinMasks::new(patterns, t.rows, t.cols)));
+
+            t.set_win_masks(m.clone());
+        }
+    }
+
+    // maps every number to each (table, cell) it appears in,
+    // so a draw only has to touch the tables that have it
+    fn build_index(&self) -> HashMap<usize, Vec<(usize, usize)>> {
+        let mut index: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+
+        for t in self.tables.iter().enumerate() {
+            for c in t.1.cells.iter().enumerate() {
+                index.entry(*c.1).or_default().push((t.0, c.0));
+            }
+        }
+
+        index
+    }
+
+    fn parse_from_file(file: File) -> Result<Self, String> {
+        Self::parse(BufReader::new(file))
+    }
+
+    // the draw order on the first line, then tables split up by empty lines
+    fn parse<R: BufRead>(reader: R) -> Result<Self, String> {
+        let mut lines = reader
+            .lines()
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| format!("can't read bingo file: {}", e))?
+            .into_iter();
         let mut bingo = Self::new();
 
-        if let Some(Ok(line)) = lines.next() {
+        if let Some(line) = lines.next().filter(|l| !l.trim().is_empty()) {
             bingo.draw_order = line
                 .split(',')
-                .filter_map(|i| i.parse::<usize>().ok())
-                .collect();
+                .map(|i| {
+                    i.trim()
+                        .parse::<usize>()
+                        .map_err(|_| format!("draw order: invalid number `{}`", i.trim()))
+                })
+                .collect::<Result<Vec<usize>, String>>()?;
         }
 
         let mut row_set: Vec<Vec<usize>> = Vec::new();
-        let mut id = 0;
-        for line in lines.flatten() {
-            if line.is_empty() {
+        // the extra empty line at the end finishes off the last table
+        for line in lines.chain(std::iter::once(String::new())) {
+            if line.trim().is_empty() {
                 if row_set.is_empty() {
                     continue;
                 }
 
-                let size = row_set[0].len();
-                let mut table = BingoTable::new(size, id);
-
-                table.populate(&row_set);
-
+                let table = BingoTable::from_rows(&row_set, bingo.tables.len())?;
                 bingo.tables.push(table);
                 row_set.clear();
 
-                id += 1;
-
                 continue;
             }
 
             // parse the rows first
-            let row: Vec<usize> = line
-                .split(' ')
-                .filter_map(|i| i.parse::<usize>().ok())
-                .collect();
+            let row = line
+                .split_whitespace()
+                .map(|i| {
+                    i.parse::<usize>().map_err(|_| {
+                        format!("table {}: invalid number `{}`", bingo.tables.len(), i)
+                    })
+                })
+                .collect::<Result<Vec<usize>, String>>()?;
 
             row_set.push(row);
         }
 
-        if !row_set.is_empty() {
-            let size = row_set[0].len();
-            let mut table = BingoTable::new(size, id);
+        Ok(bingo)
+    }
 
-            table.populate(&row_set);
+    // plays through the entire draw order from a clean slate - tables
+    // stop being marked once they've won
+    fn play(&mut self) -> BingoTimeline {
+        let mut timeline = BingoTimeline::default();
 
-            bingo.tables.push(table);
+        for t in &mut self.tables {
+            t.reset();
         }
 
-        bingo
+        let index = self.build_index();
+
+        for draw_index in 0..self.draw_order.len() {
+            let wins = self.call(&index, draw_index, self.draw_order[draw_index]);
+            timeline.wins.extend(wins);
+        }
+
+        timeline.never_won = self
+            .tables
+            .iter()
+            .filter(|t| !t.has_bingo)
+            .map(|t| t.id)
+            .collect();
+
+        timeline
     }
 
-    fn process_bingo(&mut self) -> usize {
-        for i in &self.draw_order {
-            for t in &mut self.tables {
-                let coord = t.get_cell_coords(*i);
-                if let Some(c) = coord {
-                    t.set_cell_bingopos(c, true).unwrap();
-                }
+    // marks a single number on every table that has it and hasn't
+    // won yet, returning whichever tables won because of it
+    fn call(
+        &mut self,
+        index: &HashMap<usize, Vec<(usize, usize)>>,
+        draw_index: usize,
+        n: usize,
+    ) -> Vec<BingoWin> {
+        let mut wins = Vec::new();
+
+        for (tid, cell) in index.get(&n).into_iter().flatten() {
+            let t = &mut self.tables[*tid];
+            if t.has_bingo {
+                continue;
+            }
 
-                // println!("{}", t);
+            t.mark(*cell);
 
-                if t.has_bingo {
-                    return t.calculate_score() * i;
-                }
+            if t.has_bingo {
+                let unmarked = t.calculate_score();
+
+                wins.push(BingoWin {
+                    table: t.id,
+                    pattern: t.winning_pattern.clone().unwrap(),
+                    draw_index,
+                    draw: n,
+                    unmarked,
+                    score: unmarked * n,
+                });
             }
         }
 
-        0
+        wins
     }
 
-    fn process_least_bingo(&mut self) -> usize {
-        let mut to_remove: BTreeSet<usize> = BTreeSet::new();
-        for i in &self.draw_order {
-            for t in &to_remove {
-                if let Ok(id) = self.tables.binary_search_by(|b| b.id.cmp(t)) {
-                    self.tables.remove(id);
-                }
-            }
+    fn process_bingo(&mut self) -> usize {
+        self.play().first().map_or(0, |w| w.score)
+    }
 
-            let cur_len = self.tables.len();
-            for t in self.tables.iter_mut() {
-                let coord = t.get_cell_coords(*i);
-                if let Some(c) = coord {
-                    t.set_cell_bingopos(c, true).unwrap();
-                }
+    fn process_least_bingo(&mut self) -> usize {
+        self.play().last().map_or(0, |w| w.score)
+    }
+}
 
-                // println!("{}", t);
+/// # CellMask
+///
+/// One bit per cell, row-major - so tables can have at most 128 cells.
+type CellMask = u128;
+
+/// # WinMasks
+///
+/// The cell masks of every configured pattern for some table shape,
+/// along with which pattern each one came from. Shared between every
+/// table of the same shape, since they never change.
+#[derive(Debug, PartialEq, Eq)]
+struct WinMasks {
+    patterns: Vec<WinPattern>,
+    masks: Vec<(usize, CellMask)>,
+}
 
-                if t.has_bingo {
-                    if cur_len != 1 {
-                        to_remove.insert(t.id);
-                    } else {
-                        return t.calculate_score() * i;
-                    }
-                }
-            }
+impl WinMasks {
+    fn new(patterns: &[WinPattern], rows: usize, cols: usize) -> Self {
+        let masks = patterns
+            .iter()
+            .enumerate()
+            .flat_map(|p| {
+                p.1.cell_sets(cols, rows)
+                    .into_iter()
+                    .filter_map(move |cells| {
+                        // cells outside of the table can never be marked
+                        if cells.iter().any(|c| c.0 >= cols || c.1 >= rows) {
+                            return None;
+                        }
+
+                        Some((
+                            p.0,
+                            cells.iter().fold(0, |m, c| m | cell_bit(c.1 * cols + c.0)),
+                        ))
+                    })
+            })
+            .collect();
+
+        WinMasks {
+            patterns: patterns.to_vec(),
+            masks,
         }
-
-        0
     }
 }
 
-// nested vec ugly but it works for now
+fn cell_bit(cell: usize) -> CellMask {
+    1 << cell
+}
+
 #[derive(Debug, Eq)]
 struct BingoTable {
     id: usize,
-    rows: Vec<Vec<BingoCell>>,
-    size: usize,
-    search: HashMap<usize, BingoPos>,
+    // row-major, so cell (x, y) is at y * cols + x
+    cells: Vec<usize>,
+    rows: usize,
+    cols: usize,
+    marked: CellMask,
+    // the most recently marked cell, shown as `X` when displayed
+    last_mark: Option<usize>,
     has_bingo: bool,
+    win_masks: Rc<WinMasks>,
+    winning_pattern: Option<WinPattern>,
 }
 
 type BingoPos = (usize, usize);
 type BingoCell = (usize, bool);
 
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+/// # WinPattern
+///
+/// A shape that wins a table once every cell in it is marked.
+/// Diagonal patterns only exist on square tables.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum WinPattern {
+    Row,
+    Column,
+    // top left to bottom right
+    Diagonal,
+    // top right to bottom left
+    AntiDiagonal,
+    FourCorners,
+    // both diagonals at once
+    XShape,
+    Blackout,
+    // a named, user-defined set of (x, y) cells
+    Custom(String, Vec<BingoPos>),
 }
 
-impl Direction {
-    fn to_coords(&self) -> (i8, i8) {
+impl WinPattern {
+    // every set of cells that satisfies this pattern on a cols x rows table
+    fn cell_sets(&self, cols: usize, rows: usize) -> Vec<Vec<BingoPos>> {
+        let square = cols == rows;
+        let diagonal = (0..cols).map(|i| (i, i)).collect::<Vec<BingoPos>>();
+        let anti_diagonal = (0..cols)
+            .map(|i| (cols - 1 - i, i))
+            .collect::<Vec<BingoPos>>();
+
         match self {
-            Direction::Up => (0, 1),
-            Direction::Down => (0, -1),
-            Direction::Left => (-1, 0),
-            Direction::Right => (1, 0),
+            WinPattern::Row => (0..rows)
+                .map(|y| (0..cols).map(|x| (x, y)).collect())
+                .collect(),
+            WinPattern::Column => (0..cols)
+                .map(|x| (0..rows).map(|y| (x, y)).collect())
+                .collect(),
+            WinPattern::Diagonal if square => vec![diagonal],
+            WinPattern::AntiDiagonal if square => vec![anti_diagonal],
+            WinPattern::XShape if square => {
+                let mut cells = diagonal;
+                cells.extend(anti_diagonal.into_iter().filter(|c| c.0 != c.1));
+
+                vec![cells]
+            }
+            WinPattern::Diagonal | WinPattern::AntiDiagonal | WinPattern::XShape => Vec::new(),
+            WinPattern::FourCorners => vec![vec![
+                (0, 0),
+                (cols - 1, 0),
+                (0, rows - 1),
+                (cols - 1, rows - 1),
+            ]],
+            WinPattern::Blackout => vec![(0..rows)
+                .flat_map(|y| (0..cols).map(move |x| (x, y)))
+                .collect()],
+            WinPattern::Custom(_, cells) => vec![cells.clone()],
         }
     }
-
-    fn move_coords(&self, coords: (i8, i8)) -> (i8, i8) {
-        let dir = self.to_coords();
-
-        (coords.0 + dir.0, coords.1 + dir.1)
-    }
 }
 
 impl std::fmt::Display for BingoTable {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        for r in &self.rows {
+        for y in 0..self.rows {
             write!(f, "[ ").unwrap();
-            for c in r {
-                write!(f, "{} ", if c.1 { "x" } else { "o" }).unwrap();
+            for x in 0..self.cols {
+                let c = self.get_cell(x, y).unwrap();
+                let mark = match (c.1, self.last_mark == Some(y * self.cols + x)) {
+                    (true, true) => "X",
+                    (true, false) => "x",
+                    _ => "o",
+                };
+
+                write!(f, "{} ", mark).unwrap();
             }
             writeln!(f, "]").unwrap();
         }
@@ -192,57 +371,103 @@ impl PartialEq for BingoTable {
 }
 
 impl BingoTable {
-    fn new(size: usize, id: usize) -> BingoTable {
-        let mut rows: Vec<Vec<BingoCell>> = Vec::new();
-
-        (0..size).for_each(|_| {
-            let mut row: Vec<BingoCell> = Vec::new();
-            row.resize_with(size, || (0, false));
-
-            rows.push(row);
-        });
+    fn new(rows: usize, cols: usize, id: usize) -> BingoTable {
+        if rows * cols > CellMask::BITS as usize {
+            panic!("table too large");
+        }
 
         BingoTable {
             id,
+            cells: vec![0; rows * cols],
             rows,
-            size,
-            search: HashMap::new(),
+            cols,
+            marked: 0,
+            last_mark: None,
             has_bingo: false,
+            win_masks: Rc::new(WinMasks::new(
+                &[WinPattern::Row, WinPattern::Column],
+                rows,
+                cols,
+            )),
+            winning_pattern: None,
         }
     }
 
-    // yeah
-    fn populate(&mut self, rows: &[Vec<usize>]) {
-        for i in rows.iter().enumerate() {
-            for j in i.1.iter().enumerate() {
-                self.rows[j.0][i.0] = (*j.1, false);
-                self.search.insert(*j.1, (i.0, j.0));
-            }
+    // checks that the rows actually make a table before building one
+    fn from_rows(rows: &[Vec<usize>], id: usize) -> Result<BingoTable, String> {
+        let cols = rows.first().map_or(0, |r| r.len());
+        if cols == 0 {
+            return Err(format!("table {}: empty table", id));
         }
+
+        if let Some(r) = rows.iter().position(|r| r.len() != cols) {
+            return Err(format!(
+                "table {}: row {} has {} numbers, expected {}",
+                id,
+                r,
+                rows[r].len(),
+                cols
+            ));
+        }
+
+        if rows.len() * cols > CellMask::BITS as usize {
+            return Err(format!(
+                "table {}: {}x{} is more than {} cells",
+                id,
+                rows.len(),
+                cols,
+                CellMask::BITS
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(n) = rows.iter().flatten().find(|n| !seen.insert(**n)) {
+            return Err(format!("table {}: duplicate number {}", id, n));
+        }
+
+        let mut table = BingoTable::new(rows.len(), cols, id);
+        table.populate(rows);
+
+        Ok(table)
     }
 
-    fn calculate_score(&self) -> usize {
-        self.rows
-            .iter()
-            .flatten()
-            .filter(|i| !i.1)
-            .fold(0, |r, i| r + i.0)
+    fn set_patterns(&mut self, patterns: &[WinPattern]) {
+        self.set_win_masks(Rc::new(WinMasks::new(patterns, self.rows, self.cols)));
+    }
+
+    fn set_win_masks(&mut self, win_masks: Rc<WinMasks>) {
+        self.win_masks = win_masks;
     }
 
-    /* not really needed
-    // return a vec<vec<BingoCell>> of columns
-    fn get_columns(&self) -> Vec<Vec<BingoCell>> {
-        let mut cols: Vec<Vec<BingoCell>> = Vec::new();
-        cols.resize_with(self.size, Vec::new);
+    fn populate(&mut self, rows: &[Vec<usize>]) {
+        for (y, r) in rows.iter().enumerate() {
+            for (x, n) in r.iter().enumerate() {
+                self.cells[y * self.cols + x] = *n;
+            }
+        }
+    }
 
-        for i in self.rows.iter().enumerate() {}
+    // unmarks everything
+    fn reset(&mut self) {
+        self.marked = 0;
+        self.last_mark = None;
+        self.has_bingo = false;
+        self.winning_pattern = None;
+    }
 
-        cols
+    fn calculate_score(&self) -> usize {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|c| self.marked & cell_bit(c.0) == 0)
+            .fold(0, |r, c| r + c.1)
     }
-    */
 
     fn get_cell_coords(&self, content: usize) -> Option<BingoPos> {
-        self.search.get(&content).copied()
+        self.cells
+            .iter()
+            .position(|c| *c == content)
+            .map(|c| (c % self.cols, c / self.cols))
     }
 
     fn get_cell_bingopos(&self, pos: BingoPos) -> Option<BingoCell> {
@@ -250,11 +475,13 @@ impl BingoTable {
     }
 
     fn get_cell(&self, x: usize, y: usize) -> Option<BingoCell> {
-        if let Some(r) = self.rows.get(y) {
-            r.get(x).copied()
-        } else {
-            None
+        if x >= self.cols || y >= self.rows {
+            return None;
         }
+
+        let cell = y * self.cols + x;
+
+        Some((self.cells[cell], self.marked & cell_bit(cell) != 0))
     }
 
     fn set_cell_bingopos(&mut self, pos: BingoPos, toggle: bool) -> Result<(), String> {
@@ -262,81 +489,359 @@ impl BingoTable {
     }
 
     fn set_cell(&mut self, x: usize, y: usize, toggle: bool) -> Result<(), String> {
-        if let Some(r) = self.rows.get_mut(y) {
-            if let Some(c) = r.get_mut(x) {
-                c.1 = toggle;
-                self.check_for_bingo(x, y);
-
-                Ok(())
-            } else {
-                Err("could not set cell".to_string())
-            }
+        if x >= self.cols || y >= self.rows {
+            return Err("could not set cell".to_string());
+        }
+
+        let cell = y * self.cols + x;
+        if toggle {
+            self.mark(cell);
         } else {
-            Err("could not set cell".to_string())
+            self.marked &= !cell_bit(cell);
         }
+
+        Ok(())
     }
 
-    fn check_for_bingo(&mut self, x: usize, y: usize) {
-        let ix: isize = x.try_into().unwrap();
-        let iy: isize = y.try_into().unwrap();
+    // marks a cell by its index, then checks only the
+    // masks that cell is actually a part of
+    fn mark(&mut self, cell: usize) {
+        let bit = cell_bit(cell);
+        self.marked |= bit;
+        self.last_mark = Some(cell);
 
-        if self.check_towards_horizontal(ix, iy) == self.size
-            || self.check_towards_vertical(ix, iy) == self.size
-        {
+        if self.has_bingo {
+            return;
+        }
+
+        let won = self
+            .win_masks
+            .masks
+            .iter()
+            .find(|m| m.1 & bit != 0 && self.marked & m.1 == m.1)
+            .map(|m| m.0);
+
+        if let Some(p) = won {
             self.has_bingo = true;
+            self.winning_pattern = Some(self.win_masks.patterns[p].clone());
         }
     }
+}
 
-    fn check_towards_horizontal(&self, x: isize, y: isize) -> usize {
-        if let Some(c) = self.get_cell(x as usize, y as usize) {
-            if !c.1 {
-                0
-            } else {
-                1 + self.check_towards(x, y, Direction::Left, 0)
-                    + self.check_towards(x, y, Direction::Right, 0)
-            }
-        } else {
-            0
+/// # BingoGenerator
+///
+/// Seeded generator for random games - every table gets distinct
+/// numbers out of `0..numbers`, and draw orders are shuffles of that
+/// same pool.
+#[derive(Debug)]
+struct BingoGenerator {
+    rng: Rng,
+    numbers: usize,
+    rows: usize,
+    cols: usize,
+}
+
+impl BingoGenerator {
+    fn new(seed: u64, numbers: usize, rows: usize, cols: usize) -> Self {
+        if rows * cols > numbers {
+            panic!("not enough numbers to fill a table");
+        }
+        if rows * cols > CellMask::BITS as usize {
+            panic!("tables can have at most {} cells", CellMask::BITS);
         }
+
+        BingoGenerator {
+            rng: Rng::new(seed),
+            numbers,
+            rows,
+            cols,
+        }
+    }
+
+    fn draw_order(&mut self) -> Vec<usize> {
+        let mut order = (0..self.numbers).collect::<Vec<usize>>();
+        self.rng.shuffle(&mut order);
+
+        order
+    }
+
+    fn table(&mut self, id: usize) -> BingoTable {
+        let cells = self.draw_order();
+        let rows = cells
+            .chunks(self.cols)
+            .take(self.rows)
+            .map(|r| r.to_vec())
+            .collect::<Vec<Vec<usize>>>();
+
+        BingoTable::from_rows(&rows, id).unwrap()
     }
 
-    fn check_towards_vertical(&self, x: isize, y: isize) -> usize {
-        if let Some(c) = self.get_cell(x as usize, y as usize) {
-            if !c.1 {
-                0
-            } else {
-                1 + self.check_towards(x, y, Direction::Up, 0)
-                    + self.check_towards(x, y, Direction::Down, 0)
+    fn game(&mut self, tables: usize) -> Bingo {
+        let mut bingo = Bingo::new();
+        bingo.draw_order = self.draw_order();
+        bingo.tables = (0..tables).map(|id| self.table(id)).collect();
+
+        bingo
+    }
+}
+
+/// # WinOdds
+///
+/// Estimated chance of a table being the first/last one to win.
+/// Tables that win on the same draw split the credit for it.
+#[derive(Debug, Clone, PartialEq)]
+struct WinOdds {
+    table: usize,
+    first: f64,
+    last: f64,
+}
+
+impl Bingo {
+    // replays the tables against `rounds` shuffles of the draw order,
+    // leaving the original draw order and clean tables afterwards
+    fn simulate(&mut self, rounds: usize, seed: u64) -> Vec<WinOdds> {
+        if rounds == 0 {
+            panic!("rounds must be non-zero");
+        }
+
+        let mut rng = Rng::new(seed);
+        let original = self.draw_order.clone();
+        let mut odds = self
+            .tables
+            .iter()
+            .map(|t| WinOdds {
+                table: t.id,
+                first: 0.0,
+                last: 0.0,
+            })
+            .collect::<Vec<WinOdds>>();
+        let pos = self
+            .tables
+            .iter()
+            .enumerate()
+            .map(|t| (t.1.id, t.0))
+            .collect::<HashMap<usize, usize>>();
+
+        for _ in 0..rounds {
+            rng.shuffle(&mut self.draw_order);
+            let timeline = self.play();
+
+            let (first, last) = match (timeline.first(), timeline.last()) {
+                (Some(f), Some(l)) => (f.draw_index, l.draw_index),
+                _ => continue,
+            };
+
+            for (d, is_first) in [(first, true), (last, false)] {
+                let winners = timeline
+                    .wins
+                    .iter()
+                    .filter(|w| w.draw_index == d)
+                    .collect::<Vec<&BingoWin>>();
+                let credit = 1.0 / winners.len() as f64;
+
+                for w in winners {
+                    let o = &mut odds[pos[&w.table]];
+                    if is_first {
+                        o.first += credit;
+                    } else {
+                        o.last += credit;
+                    }
+                }
             }
-        } else {
-            0
+        }
+
+        for o in &mut odds {
+            o.first /= rounds as f64;
+            o.last /= rounds as f64;
+        }
+
+        self.draw_order = original;
+        for t in &mut self.tables {
+            t.reset();
+        }
+
+        odds
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CallerCommand {
+    // call a specific number
+    Draw(usize),
+    // call the next number in the draw order
+    Next,
+    Undo,
+    // keep calling from the draw order until someone wins
+    NextWin,
+    Quit,
+}
+
+impl CallerCommand {
+    fn parse(line: &str) -> Result<Self, String> {
+        match line.trim().to_lowercase().as_str() {
+            "" | "n" | "next" => Ok(CallerCommand::Next),
+            "u" | "undo" => Ok(CallerCommand::Undo),
+            "w" | "win" => Ok(CallerCommand::NextWin),
+            "q" | "quit" => Ok(CallerCommand::Quit),
+            n => n
+                .parse::<usize>()
+                .map(CallerCommand::Draw)
+                .map_err(|_| format!("unknown command: {}", n)),
         }
     }
+}
 
-    fn check_towards(&self, x: isize, y: isize, direction: Direction, amount: usize) -> usize {
-        let (new_x, new_y) = direction.move_coords((x as i8, y as i8));
+/// # BingoCaller
+///
+/// Calls numbers one at a time - either typed in, or stepped through
+/// from the game's draw order - keeping track of what's been called so
+/// far so that draws can be taken back.
+struct BingoCaller<'a> {
+    bingo: &'a mut Bingo,
+    index: HashMap<usize, Vec<(usize, usize)>>,
+    // every number called, and whether it came from the draw order
+    called: Vec<(usize, bool)>,
+    next_draw: usize,
+}
 
-        // if any of this stuff is too much in one direction, we just return
-        // the amount we've accumulated so far
-        //
-        // also: rustfmt IMMEDIATELY does C# brackets and i Die
-        if (new_x < 0 || new_x > (self.size - 1).try_into().unwrap())
-            || (new_y < 0 || new_y > (self.size - 1).try_into().unwrap())
-        {
-            return amount;
+impl<'a> BingoCaller<'a> {
+    fn new(bingo: &'a mut Bingo) -> Self {
+        for t in &mut bingo.tables {
+            t.reset();
         }
 
-        // println!("checking: ({}, {}) | amt: {}", new_x, new_y, amount);
+        let index = bingo.build_index();
+
+        BingoCaller {
+            bingo,
+            index,
+            called: Vec::new(),
+            next_draw: 0,
+        }
+    }
+
+    fn call(&mut self, n: usize, from_order: bool) -> Vec<BingoWin> {
+        for t in &mut self.bingo.tables {
+            t.last_mark = None;
+        }
+
+        self.called.push((n, from_order));
+        if from_order {
+            self.next_draw += 1;
+        }
+
+        self.bingo.call(&self.index, self.called.len() - 1, n)
+    }
 
-        if let Some(c) = self.get_cell(new_x as usize, new_y as usize) {
-            if c.1 {
-                self.check_towards(new_x as isize, new_y as isize, direction, amount + 1)
-            } else {
-                amount
+    fn call_next(&mut self) -> Option<Vec<BingoWin>> {
+        let n = *self.bingo.draw_order.get(self.next_draw)?;
+
+        Some(self.call(n, true))
+    }
+
+    // marks can't be taken back one by one without losing track of wins,
+    // so this just replays everything but the last call
+    fn undo(&mut self) -> Option<usize> {
+        let (n, from_order) = self.called.pop()?;
+        if from_order {
+            self.next_draw -= 1;
+        }
+
+        for t in &mut self.bingo.tables {
+            t.reset();
+        }
+
+        for (i, c) in self.called.iter().enumerate() {
+            // only the very last call should end up highlighted
+            for t in &mut self.bingo.tables {
+                t.last_mark = None;
             }
-        } else {
-            amount
+
+            self.bingo.call(&self.index, i, c.0);
+        }
+
+        Some(n)
+    }
+
+    fn render<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for t in &self.bingo.tables {
+            writeln!(
+                out,
+                "table {}{}:",
+                t.id,
+                if t.has_bingo { " (bingo)" } else { "" }
+            )?;
+            write!(out, "{}", t)?;
         }
+
+        Ok(())
+    }
+
+    /// Reads commands line by line until `quit` or the input runs out,
+    /// re-rendering every table after each one.
+    fn run<R: BufRead, W: Write>(&mut self, input: R, mut out: W) -> io::Result<()> {
+        for line in input.lines() {
+            let wins = match CallerCommand::parse(&line?) {
+                Ok(CallerCommand::Draw(n)) => {
+                    writeln!(out, "called {}", n)?;
+                    self.call(n, false)
+                }
+                Ok(CallerCommand::Next) => match self.call_next() {
+                    Some(w) => {
+                        writeln!(out, "called {}", self.called.last().unwrap().0)?;
+                        w
+                    }
+                    None => {
+                        writeln!(out, "no numbers left to draw")?;
+                        continue;
+                    }
+                },
+                Ok(CallerCommand::NextWin) => {
+                    let before = self.called.len();
+                    let mut wins = Vec::new();
+                    while wins.is_empty() {
+                        match self.call_next() {
+                            Some(w) => wins = w,
+                            None => break,
+                        }
+                    }
+
+                    // only report what this command actually called
+                    if self.called.len() > before {
+                        writeln!(out, "called up to {}", self.called.last().unwrap().0)?;
+                    } else {
+                        writeln!(out, "no numbers left to draw")?;
+                    }
+
+                    wins
+                }
+                Ok(CallerCommand::Undo) => {
+                    match self.undo() {
+                        Some(n) => writeln!(out, "took back {}", n)?,
+                        None => writeln!(out, "nothing to undo")?,
+                    }
+
+                    Vec::new()
+                }
+                Ok(CallerCommand::Quit) => break,
+                Err(e) => {
+                    writeln!(out, "{}", e)?;
+                    continue;
+                }
+            };
+
+            self.render(&mut out)?;
+
+            for w in wins {
+                writeln!(
+                    out,
+                    "BINGO! table {} wins with {:?} on {} - score {}",
+                    w.table, w.pattern, w.draw, w.score
+                )?;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -355,30 +860,30 @@ mod tests {
     #[test]
     fn test_day_four() {
         let file = File::open("res/day_four.input").unwrap();
-        let mut bingo = Bingo::parse_from_file(file);
+        let mut bingo = Bingo::parse_from_file(file).unwrap();
         println!("{}", bingo.process_bingo());
 
         let file = File::open("res/day_four.input").unwrap();
-        let mut least_bingo = Bingo::parse_from_file(file);
+        let mut least_bingo = Bingo::parse_from_file(file).unwrap();
         println!("{}", least_bingo.process_least_bingo());
     }
 
     #[test]
     fn bingo_parse() {
         let file = File::open("res/day_four.test").unwrap();
-        let mut bingo = Bingo::parse_from_file(file);
+        let mut bingo = Bingo::parse_from_file(file).unwrap();
 
         println!("{}", bingo.process_bingo());
 
         let file = File::open("res/day_four.test").unwrap();
-        let mut least_bingo = Bingo::parse_from_file(file);
+        let mut least_bingo = Bingo::parse_from_file(file).unwrap();
 
         println!("{}", least_bingo.process_least_bingo());
     }
 
     #[test]
     fn bingo_table() {
-        let mut table = BingoTable::new(5, 0);
+        let mut table = BingoTable::new(5, 5, 0);
 
         table.populate(&[
             vec![22, 13, 17, 11, 0],
@@ -425,13 +930,452 @@ mod tests {
         println!("score: {}", table.calculate_score());
     }
 
+    fn example_table() -> BingoTable {
+        let mut table = BingoTable::new(5, 5, 0);
+
+        table.populate(&[
+            vec![22, 13, 17, 11, 0],
+            vec![8, 2, 23, 4, 24],
+            vec![21, 9, 14, 16, 7],
+            vec![6, 10, 3, 18, 5],
+            vec![1, 12, 20, 15, 19],
+        ]);
+
+        table
+    }
+
+    fn mark_numbers(table: &mut BingoTable, numbers: &[usize]) {
+        for n in numbers {
+            let c = table.get_cell_coords(*n).unwrap();
+            table.set_cell_bingopos(c, true).unwrap();
+        }
+    }
+
+    #[test]
+    fn pattern_cells() {
+        assert_eq!(WinPattern::Row.cell_sets(5, 5).len(), 5);
+        assert_eq!(
+            WinPattern::Column.cell_sets(4, 3)[0],
+            vec![(0, 0), (0, 1), (0, 2)]
+        );
+        assert_eq!(WinPattern::XShape.cell_sets(5, 5)[0].len(), 9);
+        assert_eq!(WinPattern::Blackout.cell_sets(4, 3)[0].len(), 12);
+        assert_eq!(
+            WinPattern::FourCorners.cell_sets(4, 3)[0],
+            vec![(0, 0), (3, 0), (0, 2), (3, 2)]
+        );
+        assert!(WinPattern::Diagonal.cell_sets(4, 3).is_empty());
+    }
+
+    #[test]
+    fn pluggable_patterns() {
+        let mut table = example_table();
+        table.set_patterns(&[WinPattern::Row, WinPattern::Column]);
+        mark_numbers(&mut table, &[22, 2, 14, 18, 19]);
+        assert!(!table.has_bingo);
+
+        let mut table = example_table();
+        table.set_patterns(&[WinPattern::Row, WinPattern::Diagonal]);
+        mark_numbers(&mut table, &[22, 2, 14, 18]);
+        assert!(!table.has_bingo);
+        mark_numbers(&mut table, &[19]);
+        assert!(table.has_bingo);
+        assert_eq!(table.winning_pattern, Some(WinPattern::Diagonal));
+
+        let mut table = example_table();
+        table.set_patterns(&[WinPattern::AntiDiagonal, WinPattern::FourCorners]);
+        mark_numbers(&mut table, &[22, 0, 1]);
+        assert!(!table.has_bingo);
+        mark_numbers(&mut table, &[19]);
+        assert_eq!(table.winning_pattern, Some(WinPattern::FourCorners));
+
+        let block = WinPattern::Custom("block".into(), vec![(0, 0), (1, 0), (0, 1), (1, 1)]);
+        let mut table = example_table();
+        table.set_patterns(&[block.clone(), WinPattern::Blackout]);
+        mark_numbers(&mut table, &[22, 13, 8]);
+        assert!(!table.has_bingo);
+        mark_numbers(&mut table, &[2]);
+        assert_eq!(table.winning_pattern, Some(block));
+    }
+
+    #[test]
+    fn bingo_win_pattern() {
+        let file = File::open("res/day_four.test").unwrap();
+        let mut bingo = Bingo::parse_from_file(file).unwrap();
+
+        let win = bingo.play().wins.remove(0);
+        assert_eq!(win.table, 2);
+        assert_eq!(win.pattern, WinPattern::Row);
+        assert_eq!(win.score, 4512);
+
+        let file = File::open("res/day_four.test").unwrap();
+        let mut bingo = Bingo::parse_from_file(file).unwrap();
+        bingo.set_patterns(&[WinPattern::Blackout]);
+
+        let win = bingo.play().wins.remove(0);
+        assert_eq!(win.pattern, WinPattern::Blackout);
+        println!("{:?}", win);
+    }
+
     #[test]
-    fn directions() {
-        let coordinates = (1, 1);
+    fn bingo_timeline() {
+        let file = File::open("res/day_four.test").unwrap();
+        let mut bingo = Bingo::parse_from_file(file).unwrap();
+
+        let timeline = bingo.play();
+        for w in &timeline.wins {
+            println!("{:?}", w);
+        }
+
+        assert_eq!(
+            timeline
+                .wins
+                .iter()
+                .map(|w| w.table)
+                .collect::<Vec<usize>>(),
+            vec![2, 0, 1]
+        );
+        assert!(timeline.never_won.is_empty());
+
+        let first = timeline.first().unwrap();
+        assert_eq!((first.draw_index, first.draw), (11, 24));
+        assert_eq!((first.unmarked, first.score), (188, 4512));
+
+        let last = timeline.last().unwrap();
+        assert_eq!((last.draw, last.unmarked, last.score), (13, 148, 1924));
+
+        // playing again starts over rather than carrying marks across
+        assert_eq!(bingo.process_bingo(), 4512);
+        assert_eq!(bingo.process_least_bingo(), 1924);
+
+        // nothing can black out before the draws run dry here
+        bingo.draw_order.truncate(12);
+        bingo.set_patterns(&[WinPattern::Blackout]);
+        let timeline = bingo.play();
+        assert!(timeline.wins.is_empty());
+        assert_eq!(timeline.never_won, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn bitmask_marking() {
+        let mut table = example_table();
+        assert_eq!(table.win_masks.masks.len(), 10);
+
+        // the last row
+        for x in 0..5 {
+            table.mark(4 * 5 + x);
+        }
+
+        assert!(table.has_bingo);
+        assert_eq!(table.marked.count_ones(), 5);
+        assert_eq!(table.calculate_score(), 300 - (1 + 12 + 20 + 15 + 19));
+    }
+
+    #[test]
+    fn shared_win_masks() {
+        let file = File::open("res/day_four.test").unwrap();
+        let mut bingo = Bingo::parse_from_file(file).unwrap();
+        bingo.set_patterns(&[WinPattern::Diagonal]);
+
+        assert!(Rc::ptr_eq(
+            &bingo.tables[0].win_masks,
+            &bingo.tables[2].win_masks
+        ));
+
+        let index = bingo.build_index();
+        assert_eq!(index.get(&26).map(|v| v.len()), Some(1));
+        assert_eq!(index.get(&26).map(|v| v[0].0), Some(2));
+        assert_eq!(index.get(&0).map(|v| v.len()), Some(3));
+        assert_eq!(index.get(&99), None);
+    }
+
+    // run with `cargo test --release bench_many_tables -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_many_tables() {
+        use std::time::Instant;
+
+        for count in [1_000, 10_000, 100_000, 500_000] {
+            let mut bingo = Bingo::new();
+            // any multiplier mod a prime gives distinct cells, and different
+            // offsets give different tables
+            bingo.draw_order = (0..101).map(|i| (37 * i + 11) % 101).collect();
+            for id in 0..count {
+                let (a, b) = (1 + id % 100, id / 100 % 101);
+                let mut table = BingoTable::new(5, 5, id);
+                table.populate(
+                    &(0..5)
+                        .map(|r| (0..5).map(|c| (a * (r * 5 + c) + b) % 101).collect())
+                        .collect::<Vec<Vec<usize>>>(),
+                );
+
+                bingo.tables.push(table);
+            }
+
+            let start = Instant::now();
+            let timeline = bingo.play();
+
+            println!(
+                "{} tables: {} wins in {:?}",
+                count,
+                timeline.wins.len(),
+                start.elapsed()
+            );
+        }
+    }
+
+    #[test]
+    fn table_orientation() {
+        let table = BingoTable::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]], 0).unwrap();
+
+        assert_eq!((table.rows, table.cols), (2, 3));
+        assert_eq!(table.get_cell(2, 0), Some((3, false)));
+        assert_eq!(table.get_cell(0, 1), Some((4, false)));
+        assert_eq!(table.get_cell(0, 2), None);
+        assert_eq!(table.get_cell_coords(6), Some((2, 1)));
+        println!("{}", table);
+    }
+
+    #[test]
+    fn non_square_tables() {
+        let mut table = BingoTable::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]], 0).unwrap();
+        assert_eq!(table.win_masks.masks.len(), 5);
+
+        mark_numbers(&mut table, &[3, 6]);
+        assert_eq!(table.winning_pattern, Some(WinPattern::Column));
+
+        let mut table = BingoTable::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]], 0).unwrap();
+        table.set_patterns(&[WinPattern::Diagonal, WinPattern::FourCorners]);
+        assert_eq!(table.win_masks.masks.len(), 1);
+
+        mark_numbers(&mut table, &[1, 3, 4]);
+        assert!(!table.has_bingo);
+        mark_numbers(&mut table, &[6]);
+        assert_eq!(table.winning_pattern, Some(WinPattern::FourCorners));
+    }
+
+    #[test]
+    fn mixed_table_sizes() {
+        let mut bingo = Bingo::new();
+        bingo.draw_order = vec![7, 1, 2, 9, 3];
+        bingo.tables = vec![
+            BingoTable::from_rows(&[vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]], 0).unwrap(),
+            BingoTable::from_rows(&[vec![1, 2], vec![3, 4]], 1).unwrap(),
+            BingoTable::from_rows(&[vec![7], vec![8]], 2).unwrap(),
+        ];
+        bingo.set_patterns(&[WinPattern::Row, WinPattern::Diagonal]);
+
+        let timeline = bingo.play();
+        let wins = timeline
+            .wins
+            .iter()
+            .map(|w| (w.table, w.draw, w.pattern.clone()))
+            .collect::<Vec<(usize, usize, WinPattern)>>();
+
+        assert_eq!(
+            wins,
+            vec![
+                (2, 7, WinPattern::Row),
+                (1, 2, WinPattern::Row),
+                (0, 3, WinPattern::Row)
+            ]
+        );
+    }
+
+    #[test]
+    fn table_parse_errors() {
+        assert_eq!(
+            BingoTable::from_rows(&[vec![1, 2, 3], vec![4, 5]], 3).unwrap_err(),
+            "table 3: row 1 has 2 numbers, expected 3"
+        );
+        assert_eq!(
+            BingoTable::from_rows(&[vec![1, 2], vec![2, 5]], 0).unwrap_err(),
+            "table 0: duplicate number 2"
+        );
+        assert!(BingoTable::from_rows(&vec![(0..12).collect(); 12], 0).is_err());
+        assert!(BingoTable::from_rows(&[], 0).is_err());
+
+        let parse = |contents: &str| Bingo::parse(io::Cursor::new(contents));
+
+        assert_eq!(
+            parse("1,2,x,4\n\n1 2\n3 4\n").unwrap_err(),
+            "draw order: invalid number `x`"
+        );
+        assert_eq!(
+            parse("1,2\n\n1 2\n3 y\n").unwrap_err(),
+            "table 0: invalid number `y`"
+        );
+        assert!(parse("1,2\n\n1 2\n3\n").is_err());
+
+        let bingo = parse("4, 3,2\n\n1 2\n3 4\n").unwrap();
+        assert_eq!(bingo.draw_order, vec![4, 3, 2]);
+        assert_eq!(bingo.tables.len(), 1);
+    }
+
+    #[test]
+    fn generated_games() {
+        let mut generator = BingoGenerator::new(2021, 100, 5, 5);
+        let bingo = generator.game(20);
+
+        assert_eq!(bingo.tables.len(), 20);
+        assert_eq!(bingo.draw_order.len(), 100);
+        assert!(bingo
+            .tables
+            .iter()
+            .all(|t| t.cells.iter().all(|c| *c < 100)));
+
+        // same seed, same game
+        let again = BingoGenerator::new(2021, 100, 5, 5).game(20);
+        assert_eq!(bingo.draw_order, again.draw_order);
+        assert!(bingo
+            .tables
+            .iter()
+            .zip(again.tables.iter())
+            .all(|t| t.0.cells == t.1.cells));
+
+        let small = BingoGenerator::new(1, 12, 3, 4).table(0);
+        assert_eq!((small.rows, small.cols), (3, 4));
+    }
+
+    #[test]
+    fn monte_carlo_odds() {
+        let mut bingo = BingoGenerator::new(7, 50, 3, 3).game(8);
+        let order = bingo.draw_order.clone();
+
+        let odds = bingo.simulate(2000, 99);
+        for o in &odds {
+            println!("{:?}", o);
+        }
+
+        assert_eq!(bingo.draw_order, order);
+        assert!((odds.iter().map(|o| o.first).sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!((odds.iter().map(|o| o.last).sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(odds, bingo.simulate(2000, 99));
+        assert!(bingo.tables.iter().all(|t| t.marked == 0 && !t.has_bingo));
+    }
+
+    #[test]
+    #[should_panic(expected = "rounds must be non-zero")]
+    fn monte_carlo_no_rounds() {
+        BingoGenerator::new(7, 50, 3, 3).game(8).simulate(0, 99);
+    }
+
+    #[test]
+    #[should_panic(expected = "tables can have at most 128 cells")]
+    fn generator_too_many_cells() {
+        BingoGenerator::new(1, 200, 12, 12).game(1);
+    }
+
+    #[test]
+    fn highlighted_display() {
+        let mut table = BingoTable::from_rows(&[vec![1, 2], vec![3, 4]], 0).unwrap();
+        mark_numbers(&mut table, &[1, 4]);
+
+        assert_eq!(table.to_string(), "[ x o ]\n[ o X ]\n");
+    }
+
+    #[test]
+    fn caller_commands() {
+        let file = File::open("res/day_four.test").unwrap();
+        let mut bingo = Bingo::parse_from_file(file).unwrap();
+        let mut caller = BingoCaller::new(&mut bingo);
+
+        assert_eq!(caller.call_next().unwrap(), Vec::new());
+        assert_eq!(caller.call(26, false), Vec::new());
+        assert_eq!(caller.undo(), Some(26));
+        assert_eq!(caller.undo(), Some(7));
+        assert_eq!(caller.undo(), None);
+        assert_eq!(caller.next_draw, 0);
+
+        let mut wins = Vec::new();
+        while wins.is_empty() {
+            wins = caller.call_next().unwrap();
+        }
+        assert_eq!(wins[0].score, 4512);
+
+        // undoing the winning draw takes the win back too
+        caller.undo();
+        assert!(caller.bingo.tables.iter().all(|t| !t.has_bingo));
+        assert_eq!(caller.call_next().unwrap()[0].score, 4512);
+    }
+
+    #[test]
+    fn caller_undo_highlight() {
+        let mut bingo = Bingo {
+            draw_order: Vec::new(),
+            tables: vec![
+                BingoTable::from_rows(&[vec![1, 2], vec![3, 4]], 0).unwrap(),
+                BingoTable::from_rows(&[vec![5, 6], vec![7, 8]], 1).unwrap(),
+            ],
+        };
+        let mut caller = BingoCaller::new(&mut bingo);
+
+        caller.call(1, false);
+        caller.call(5, false);
+        caller.call(9, false);
+        assert_eq!(caller.bingo.tables[0].to_string(), "[ x o ]\n[ o o ]\n");
+
+        // 5 is the last call now, and it isn't on table 0
+        assert_eq!(caller.undo(), Some(9));
+        assert_eq!(caller.bingo.tables[0].to_string(), "[ x o ]\n[ o o ]\n");
+        assert_eq!(caller.bingo.tables[1].to_string(), "[ X o ]\n[ o o ]\n");
+    }
+
+    #[test]
+    fn caller_session() {
+        let file = File::open("res/day_four.test").unwrap();
+        let mut bingo = Bingo::parse_from_file(file).unwrap();
+        let mut out: Vec<u8> = Vec::new();
+
+        BingoCaller::new(&mut bingo)
+            .run(
+                io::Cursor::new("next\n14\nundo\nwin\nwat\nquit\nnext\n"),
+                &mut out,
+            )
+            .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        println!("{}", out);
+
+        assert!(out.starts_with("called 7\ntable 0:\n[ o o o o o ]\n[ o o o o o ]\n[ o o o o X ]"));
+        assert!(out.contains("called 14\n"));
+        assert!(out.contains("took back 14\n"));
+        assert!(out.contains("called up to 24\n"));
+        assert!(out.contains("table 2 (bingo):\n[ x x x X x ]"));
+        assert!(out.contains("BINGO! table 2 wins with Row on 24 - score 4512\n"));
+        assert!(out.ends_with("unknown command: wat\n"));
+    }
+
+    #[test]
+    fn caller_session_draws_run_out() {
+        let file = File::open("res/day_four.test").unwrap();
+        let mut bingo = Bingo::parse_from_file(file).unwrap();
+        let draws = bingo.draw_order.len();
+        let mut out: Vec<u8> = Vec::new();
+
+        // three wins, one more that draws out the rest, then nothing
+        BingoCaller::new(&mut bingo)
+            .run(io::Cursor::new("win\n".repeat(5)), &mut out)
+            .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        println!("{}", out);
+
+        assert_eq!(out.matches("called up to").count(), 4);
+        assert!(out.contains(&format!("called up to {}\n", bingo.draw_order[draws - 1])));
+        assert_eq!(out.matches("no numbers left to draw\n").count(), 1);
+    }
+
+    // run with `cargo test interactive_caller -- --ignored`, then type
+    // numbers, `next`, `undo`, `win` or `quit`
+    #[test]
+    #[ignore]
+    fn interactive_caller() {
+        let file = File::open("res/day_four.test").unwrap();
+        let mut bingo = Bingo::parse_from_file(file).unwrap();
 
-        assert_eq!(Direction::Up.move_coords(coordinates), (1, 2));
-        assert_eq!(Direction::Down.move_coords(coordinates), (1, 0));
-        assert_eq!(Direction::Left.move_coords(coordinates), (0, 1));
-        assert_eq!(Direction::Right.move_coords(coordinates), (2, 1));
+        BingoCaller::new(&mut bingo)
+            .run(io::stdin().lock(), io::stdout())
+            .unwrap();
     }
 }
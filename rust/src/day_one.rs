@@ -1,45 +1,133 @@
-pub fn count_increases(list: &[isize]) -> isize {
-    let mut inc: isize = 0;
-    let mut prev: Option<isize> = None;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+/// # SlidingWindow
+///
+/// Iterator over the sums of every `size`-wide window of some
+/// other iterator. The sum is kept rolling, so each item only costs
+/// one addition and one subtraction, and only `size` measurements
+/// are ever held at once - this works fine over unbounded input.
+pub struct SlidingWindow<I> {
+    iter: I,
+    size: usize,
+    window: VecDeque<isize>,
+    sum: isize,
+}
+
+impl<I: Iterator<Item = isize>> SlidingWindow<I> {
+    pub fn new(iter: I, size: usize) -> Self {
+        if size == 0 {
+            panic!("window size must be non-zero");
+        }
+
+        SlidingWindow {
+            iter,
+            size,
+            window: VecDeque::with_capacity(size + 1),
+            sum: 0,
+        }
+    }
+}
+
+impl<I: Iterator<Item = isize>> Iterator for SlidingWindow<I> {
+    type Item = isize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // fill up the window first, then slide it by one each time
+        while self.window.len() < self.size {
+            let m = self.iter.next()?;
+            self.window.push_back(m);
+            self.sum += m;
 
-    for i in list {
-        if let Some(x) = prev {
-            if *i > x {
-                inc += 1;
+            if self.window.len() == self.size {
+                return Some(self.sum);
             }
         }
 
-        prev = Some(*i);
+        let m = self.iter.next()?;
+        self.window.push_back(m);
+        self.sum += m - self.window.pop_front().unwrap();
+
+        Some(self.sum)
     }
+}
 
-    inc
+/// # WindowReport
+///
+/// How consecutive window sums compared against each other.
+/// `longest_run` is the amount of windows in the longest strictly
+/// increasing stretch (a single window counts as a run of one).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WindowReport {
+    pub increases: usize,
+    pub decreases: usize,
+    pub unchanged: usize,
+    pub longest_run: usize,
+    current_run: usize,
+    prev: Option<isize>,
 }
 
-pub fn count_increases_in_threes(list: &[isize]) -> isize {
-    let mut inc: isize = 0;
-    let mut prev: Option<isize> = None;
-
-    let mut i = 0;
-    while i + 2 < list.len() {
-        let cur: isize = list[i..i + 3].iter().sum();
-        if let Some(x) = prev {
-            if cur > x {
-                inc += 1;
+impl WindowReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the next window sum into the report.
+    pub fn push(&mut self, sum: isize) {
+        match self.prev.map(|p| sum.cmp(&p)) {
+            Some(Ordering::Greater) => {
+                self.increases += 1;
+                self.current_run += 1;
             }
+            Some(Ordering::Less) => {
+                self.decreases += 1;
+                self.current_run = 1;
+            }
+            Some(Ordering::Equal) => {
+                self.unchanged += 1;
+                self.current_run = 1;
+            }
+            None => self.current_run = 1,
         }
 
-        i += 1;
-        prev = Some(cur);
+        self.longest_run = self.longest_run.max(self.current_run);
+        self.prev = Some(sum);
     }
+}
+
+/// Compares every `size`-wide window of `list` against the one before it.
+pub fn analyze_windows<I: IntoIterator<Item = isize>>(list: I, size: usize) -> WindowReport {
+    SlidingWindow::new(list.into_iter(), size).fold(WindowReport::new(), |mut r, s| {
+        r.push(s);
+        r
+    })
+}
 
-    inc
+/// Reads measurements line by line, skipping anything that isn't a number.
+/// Lazy, so this can be handed `stdin().lock()` directly.
+pub fn read_measurements<R: BufRead>(reader: R) -> impl Iterator<Item = isize> {
+    reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|l| l.trim().parse::<isize>().ok())
+}
+
+pub fn count_increases(list: &[isize]) -> isize {
+    analyze_windows(list.iter().copied(), 1).increases as isize
+}
+
+pub fn count_increases_in_threes(list: &[isize]) -> isize {
+    analyze_windows(list.iter().copied(), 3).increases as isize
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs::File;
-    use std::io::Read;
+    use std::io::{BufReader, Read};
+
+    const EXAMPLE: [isize; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
 
     #[test]
     fn test_day_one() {
@@ -59,4 +147,38 @@ mod tests {
 
         println!("part two: {}", count_increases_in_threes(&num_vec));
     }
+
+    #[test]
+    fn sliding_window_sums() {
+        let sums = SlidingWindow::new(EXAMPLE.iter().copied(), 3).collect::<Vec<isize>>();
+
+        assert_eq!(sums, vec![607, 618, 618, 617, 647, 716, 769, 792]);
+        assert_eq!(SlidingWindow::new([1, 2].iter().copied(), 3).count(), 0);
+    }
+
+    #[test]
+    fn window_report() {
+        assert_eq!(count_increases(&EXAMPLE), 7);
+        assert_eq!(count_increases_in_threes(&EXAMPLE), 5);
+
+        let report = analyze_windows(EXAMPLE.iter().copied(), 3);
+        println!("{:?}", report);
+
+        assert_eq!(report.increases, 5);
+        assert_eq!(report.decreases, 1);
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.longest_run, 5);
+    }
+
+    #[test]
+    fn streaming_measurements() {
+        let file = File::open("res/day_one.input").unwrap();
+        let report = analyze_windows(read_measurements(BufReader::new(file)), 3);
+
+        println!("{:?}", report);
+
+        let report = analyze_windows((0..).take(100_000), 1000);
+        assert_eq!(report.increases, 99_000);
+        assert_eq!(report.longest_run, 99_001);
+    }
 }
@@ -1,8 +1,88 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 
+use crate::util::Coordinate;
+
+/// # FuelCost
+///
+/// How much fuel a crab burns to move some distance. Every model has to
+/// be convex and not go down as the distance grows, custom ones too -
+/// the optimiser relies on the total cost only having one valley.
+enum FuelCost {
+    Linear,
+    Triangular,
+    Quadratic,
+    Custom(String, Box<dyn Fn(usize) -> usize>),
+}
+
+impl FuelCost {
+    fn name(&self) -> &str {
+        match self {
+            FuelCost::Linear => "linear",
+            FuelCost::Triangular => "triangular",
+            FuelCost::Quadratic => "quadratic",
+            FuelCost::Custom(name, _) => name,
+        }
+    }
+
+    fn cost(&self, distance: usize) -> usize {
+        match self {
+            FuelCost::Linear => distance,
+            FuelCost::Triangular => distance * (distance + 1) / 2,
+            FuelCost::Quadratic => distance * distance,
+            FuelCost::Custom(_, f) => f(distance),
+        }
+    }
+
+    // the same thing for distances that aren't whole numbers, custom
+    // costs are interpolated between the whole distances around it
+    fn cost_real(&self, distance: f64) -> f64 {
+        match self {
+            FuelCost::Linear => distance,
+            FuelCost::Triangular => distance * (distance + 1.0) / 2.0,
+            FuelCost::Quadratic => distance * distance,
+            FuelCost::Custom(_, f) => {
+                let whole = distance.floor();
+                let below = f(whole as usize) as f64;
+                below + (distance - whole) * self.slope_real(distance)
+            }
+        }
+    }
+
+    // how fast the cost goes up at some distance
+    fn slope_real(&self, distance: f64) -> f64 {
+        match self {
+            FuelCost::Linear => 1.0,
+            FuelCost::Triangular => distance + 0.5,
+            FuelCost::Quadratic => 2.0 * distance,
+            FuelCost::Custom(_, f) => {
+                let whole = distance.floor() as usize;
+                f(whole + 1) as f64 - f(whole) as f64
+            }
+        }
+    }
+}
+
+/// # Alignment
+///
+/// The cheapest positions to line every crab up at (in order, more
+/// than one if they tie) and what that costs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Alignment {
+    positions: Vec<usize>,
+    cost: usize,
+}
+
+impl Alignment {
+    fn position(&self) -> usize {
+        self.positions[0]
+    }
+
+    fn is_tied(&self) -> bool {
+        self.positions.len() > 1
+    }
+}
+
 struct CrabSubmarines {
     crab_pos: Vec<usize>,
 }
@@ -27,59 +107,512 @@ impl CrabSubmarines {
         Self::new(crab_pos)
     }
 
-    fn get_all_possible_vals(&mut self) -> (usize, usize) {
-        self.crab_pos.sort_unstable();
-        let mut v = (0..=self.crab_pos[self.crab_pos.len() - 1] * 2)
-            .map(|v| {
-                (
-                    v,
-                    self.crab_pos.iter().fold(0, |acc, x| match x.cmp(&v) {
-                        Ordering::Greater => acc + ((x - v) * ((x - v) + 1) / 2),
-                        Ordering::Less => acc + ((v - x) * ((v - x) + 1) / 2),
-                        Ordering::Equal => acc,
-                    }),
-                )
+    fn total_cost(&self, cost: &FuelCost, position: usize) -> usize {
+        self.crab_pos
+            .iter()
+            .map(|c| {
+                cost.cost(if *c > position {
+                    c - position
+                } else {
+                    position - c
+                })
             })
+            .sum()
+    }
+
+    /// Finds the cheapest position(s) to align at. The optimum is always
+    /// somewhere between the leftmost and rightmost crab.
+    ///
+    /// Linear cost is cheapest at the median, quadratic at the mean, and
+    /// triangular within half a step of the mean, so only a couple of
+    /// positions there need checking. Custom costs get a binary search on
+    /// the slope of the total cost (a ternary search, but with integers).
+    /// From the best one found, ties are picked up by walking outwards,
+    /// which works because the total cost is convex.
+    fn optimise(&self, cost: &FuelCost) -> Option<Alignment> {
+        let min = *self.crab_pos.iter().min()?;
+        let max = *self.crab_pos.iter().max()?;
+        let n = self.crab_pos.len();
+
+        let (lo, hi) = match cost {
+            FuelCost::Linear => {
+                let mut sorted = self.crab_pos.clone();
+                sorted.sort_unstable();
+                (sorted[(n - 1) / 2], sorted[n / 2])
+            }
+            FuelCost::Triangular | FuelCost::Quadratic => {
+                let sum = self.crab_pos.iter().sum::<usize>();
+                let mean = sum / n;
+                (mean.saturating_sub(1).max(min), (mean + 2).min(max))
+            }
+            FuelCost::Custom(..) => {
+                let (mut lo, mut hi) = (min, max);
+                while lo < hi {
+                    let mid = lo + (hi - lo) / 2;
+                    if self.total_cost(cost, mid + 1) < self.total_cost(cost, mid) {
+                        lo = mid + 1;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                (lo, lo)
+            }
+        };
+
+        let (mut best, best_cost) = (lo..=hi)
+            .map(|p| (p, self.total_cost(cost, p)))
+            .min_by_key(|(_, c)| *c)
+            .unwrap();
+
+        while best > min && self.total_cost(cost, best - 1) == best_cost {
+            best -= 1;
+        }
+
+        let mut positions = vec![best];
+        while best < max && self.total_cost(cost, best + 1) == best_cost {
+            best += 1;
+            positions.push(best);
+        }
+
+        Some(Alignment {
+            positions,
+            cost: best_cost,
+        })
+    }
+
+    /// Every position from the leftmost to the rightmost crab, for
+    /// checking `optimise` against.
+    fn optimise_exhaustive(&self, cost: &FuelCost) -> Option<Alignment> {
+        let min = *self.crab_pos.iter().min()?;
+        let max = *self.crab_pos.iter().max()?;
+        let costs = (min..=max)
+            .map(|p| (p, self.total_cost(cost, p)))
             .collect::<Vec<(usize, usize)>>();
+        let best_cost = costs.iter().map(|(_, c)| *c).min()?;
 
-        v.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+        Some(Alignment {
+            positions: costs
+                .iter()
+                .filter(|(_, c)| *c == best_cost)
+                .map(|(p, _)| *p)
+                .collect(),
+            cost: best_cost,
+        })
+    }
+
+    // (position, cost) with triangular cost
+    fn get_all_possible_vals(&mut self) -> (usize, usize) {
+        let alignment = self.optimise(&FuelCost::Triangular).unwrap();
 
-        v[v.len() - 1]
+        (alignment.position(), alignment.cost)
     }
 
     fn get_efficient_crabs(&mut self) -> usize {
-        let mut crab_map: HashMap<usize, usize> = HashMap::new();
-        for c in &self.crab_pos {
-            match crab_map.get(c) {
-                Some(p) => {
-                    let new_p = p + 1;
-                    crab_map.insert(*c, new_p);
-                }
-                None => {
-                    crab_map.insert(*c, 1);
-                }
+        self.optimise(&FuelCost::Linear).unwrap().cost
+    }
+}
+
+/// # CostCurve
+///
+/// The total cost of aligning at every position from the leftmost to
+/// the rightmost crab, in order.
+///
+/// For the built in models this keeps the sums of the distances (and of
+/// the squared distances) to the crabs on either side, and updates them
+/// as it moves one position along, so the whole curve takes O(n + range).
+/// Custom models can't be split up like that and cost O(n) per position.
+struct CostCurve<'a> {
+    crabs: &'a CrabSubmarines,
+    cost: &'a FuelCost,
+    min: usize,
+    // crabs per position, starting at min
+    counts: Vec<usize>,
+    position: usize,
+    // amount of crabs and sum of distance and distance^2 on either side,
+    // the left side includes the current position
+    left: (usize, usize, usize),
+    right: (usize, usize, usize),
+}
+
+impl<'a> CostCurve<'a> {
+    fn new(crabs: &'a CrabSubmarines, cost: &'a FuelCost) -> Self {
+        let min = crabs.crab_pos.iter().min().copied().unwrap_or(0);
+        let max = crabs.crab_pos.iter().max().copied().unwrap_or(0);
+
+        let mut counts = vec![
+            0;
+            if crabs.crab_pos.is_empty() {
+                0
+            } else {
+                max - min + 1
+            }
+        ];
+        let mut right = (0, 0, 0);
+        for c in crabs.crab_pos.iter() {
+            let d = c - min;
+            counts[d] += 1;
+
+            if d > 0 {
+                right.0 += 1;
+                right.1 += d;
+                right.2 += d * d;
+            }
+        }
+
+        CostCurve {
+            crabs,
+            cost,
+            min,
+            left: (counts.first().copied().unwrap_or(0), 0, 0),
+            counts,
+            position: min,
+            right,
+        }
+    }
+
+    fn current(&self) -> usize {
+        let linear = self.left.1 + self.right.1;
+        let quadratic = self.left.2 + self.right.2;
+
+        match self.cost {
+            FuelCost::Linear => linear,
+            FuelCost::Triangular => (quadratic + linear) / 2,
+            FuelCost::Quadratic => quadratic,
+            FuelCost::Custom(..) => self.crabs.total_cost(self.cost, self.position),
+        }
+    }
+
+    fn advance(&mut self) {
+        // everything on the left gets one further away
+        let (n, s1, s2) = self.left;
+        self.left = (n, s1 + n, s2 + 2 * s1 + n);
+
+        // the crabs on the next position are at distance 1, and move over
+        // to the left at distance 0
+        let arriving = self.counts[self.position + 1 - self.min];
+        self.left.0 += arriving;
+
+        let (n, s1, s2) = self.right;
+        let (n, s1, s2) = (n - arriving, s1 - arriving, s2 - arriving);
+        self.right = (n, s1 - n, s2 + n - 2 * s1);
+
+        self.position += 1;
+    }
+}
+
+impl Iterator for CostCurve<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.min + self.counts.len() {
+            return None;
+        }
+
+        let item = (self.position, self.current());
+
+        if self.position + 1 < self.min + self.counts.len() {
+            self.advance();
+        } else {
+            self.position += 1;
+        }
+
+        Some(item)
+    }
+}
+
+impl CrabSubmarines {
+    fn cost_curve<'a>(&'a self, cost: &'a FuelCost) -> CostCurve<'a> {
+        CostCurve::new(self, cost)
+    }
+
+    fn curve_to_csv(&self, cost: &FuelCost) -> String {
+        let mut res = format!("position,{}\n", cost.name());
+        for (p, c) in self.cost_curve(cost) {
+            res.push_str(&format!("{},{}\n", p, c));
+        }
+
+        res
+    }
+
+    /// Plots the curve as `height` rows of text, at most `width` wide.
+    /// If there are more positions than columns, each column shows the
+    /// cheapest of the positions it covers. The cost axis is scaled from
+    /// the cheapest to the most expensive position, so flat stretches
+    /// near the optimum show up as one long bottom row.
+    fn plot_curve(&self, cost: &FuelCost, width: usize, height: usize) -> String {
+        if width == 0 || height == 0 {
+            panic!("plot must be at least 1x1");
+        }
+
+        let curve = self
+            .cost_curve(cost)
+            .map(|(_, c)| c)
+            .collect::<Vec<usize>>();
+        let columns = curve
+            .chunks(curve.len().div_ceil(width).max(1))
+            .map(|c| *c.iter().min().unwrap())
+            .collect::<Vec<usize>>();
+
+        let lo = columns.iter().min().copied().unwrap_or(0);
+        let hi = columns.iter().max().copied().unwrap_or(0);
+        let level = |c: usize| {
+            if hi == lo {
+                0
+            } else {
+                (c - lo) * (height - 1) / (hi - lo)
+            }
+        };
+
+        let mut plot = String::new();
+        for row in (0..height).rev() {
+            let line = columns
+                .iter()
+                .map(|c| if level(*c) == row { '*' } else { ' ' })
+                .collect::<String>();
+
+            plot.push_str(line.trim_end());
+            plot.push('\n');
+        }
+
+        plot
+    }
+}
+
+/// # Metric
+///
+/// How distance is measured between crabs that can move in two
+/// directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    Manhattan,
+    Euclidean,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PlanarCrab {
+    position: Coordinate,
+    // how many crabs this one counts as
+    weight: usize,
+}
+
+/// # PlanarAlignment
+///
+/// Where crabs on a plane should meet. With Manhattan distance this is
+/// always a whole position, with Euclidean distance it almost never is.
+#[derive(Debug, Clone, PartialEq)]
+struct PlanarAlignment {
+    x: f64,
+    y: f64,
+    cost: f64,
+}
+
+/// # PlanarCrabs
+///
+/// Crabs that aren't stuck on a line. The cost models are the same as
+/// in one dimension, just applied to the distance on the plane and
+/// multiplied by each crab's weight.
+struct PlanarCrabs {
+    crabs: Vec<PlanarCrab>,
+}
+
+impl PlanarCrabs {
+    fn new(crabs: Vec<PlanarCrab>) -> Self {
+        PlanarCrabs { crabs }
+    }
+
+    /// One crab per line, as `x,y` or `x,y,weight`.
+    fn parse(input: &str) -> Result<Self, String> {
+        let mut crabs = Vec::new();
+
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let fields = line
+                .split(',')
+                .map(|f| f.trim().parse::<isize>())
+                .collect::<Result<Vec<isize>, _>>()
+                .map_err(|e| format!("{}: {}", line, e))?;
+
+            let weight = match fields.len() {
+                2 => 1,
+                3 if fields[2] >= 0 => fields[2] as usize,
+                3 => return Err(format!("{}: negative weight", line)),
+                _ => return Err(format!("{}: expected x,y or x,y,weight", line)),
             };
+
+            crabs.push(PlanarCrab {
+                position: (fields[0], fields[1]),
+                weight,
+            });
         }
 
-        let mut v = crab_map.into_iter().collect::<Vec<(usize, usize)>>();
+        Ok(Self::new(crabs))
+    }
 
-        v.sort_by(|a, b| a.1.cmp(&b.1));
-        // println!("{:?}", v);
+    fn new_from_file(file: &mut File) -> Result<Self, String> {
+        let mut res = String::new();
+        file.read_to_string(&mut res).map_err(|e| e.to_string())?;
 
-        let efficient_val = v[v.len() - 1].0;
-        self.crab_pos
+        Self::parse(&res)
+    }
+
+    fn distance(metric: Metric, a: (f64, f64), b: (f64, f64)) -> f64 {
+        let (dx, dy) = ((a.0 - b.0).abs(), (a.1 - b.1).abs());
+
+        match metric {
+            Metric::Manhattan => dx + dy,
+            Metric::Euclidean => dx.hypot(dy),
+        }
+    }
+
+    fn total_cost(&self, metric: Metric, cost: &FuelCost, x: f64, y: f64) -> f64 {
+        self.crabs
             .iter()
-            .fold(0, |acc, x| match x.cmp(&efficient_val) {
-                Ordering::Greater => acc + (x - efficient_val),
-                Ordering::Less => acc + (efficient_val - x),
-                Ordering::Equal => acc,
+            .map(|c| {
+                let p = (c.position.0 as f64, c.position.1 as f64);
+                c.weight as f64 * cost.cost_real(Self::distance(metric, (x, y), p))
             })
+            .sum()
+    }
+
+    // exact cost of meeting at a whole position with Manhattan distance
+    fn manhattan_cost(&self, cost: &FuelCost, x: isize, y: isize) -> usize {
+        self.crabs
+            .iter()
+            .map(|c| {
+                let d = (c.position.0 - x).unsigned_abs() + (c.position.1 - y).unsigned_abs();
+                c.weight * cost.cost(d)
+            })
+            .sum()
+    }
+
+    fn bounds(&self) -> Option<(Coordinate, Coordinate)> {
+        let xs = self.crabs.iter().map(|c| c.position.0);
+        let ys = self.crabs.iter().map(|c| c.position.1);
+
+        Some((
+            (xs.clone().min()?, ys.clone().min()?),
+            (xs.max()?, ys.max()?),
+        ))
+    }
+
+    fn optimise(&self, metric: Metric, cost: &FuelCost) -> Option<PlanarAlignment> {
+        if self.crabs.iter().all(|c| c.weight == 0) {
+            return None;
+        }
+
+        let (x, y) = match (metric, cost) {
+            (Metric::Manhattan, FuelCost::Linear) => self.axis_medians(),
+            (Metric::Manhattan, _) => self.manhattan_search(cost),
+            (Metric::Euclidean, _) => self.weiszfeld(cost),
+        };
+
+        Some(PlanarAlignment {
+            x,
+            y,
+            cost: self.total_cost(metric, cost, x, y),
+        })
+    }
+
+    /// With linear cost, Manhattan distance splits into one problem per
+    /// axis, and each of those is solved by the (weighted) median.
+    fn axis_medians(&self) -> (f64, f64) {
+        let total = self.crabs.iter().map(|c| c.weight).sum::<usize>();
+        let median = |axis: fn(&PlanarCrab) -> isize| {
+            let mut sorted = self.crabs.iter().collect::<Vec<&PlanarCrab>>();
+            sorted.sort_by_key(|c| axis(c));
+
+            let mut seen = 0;
+            for c in sorted {
+                seen += c.weight;
+                if seen * 2 >= total {
+                    return axis(c) as f64;
+                }
+            }
+
+            unreachable!()
+        };
+
+        (median(|c| c.position.0), median(|c| c.position.1))
+    }
+
+    /// Anything else with Manhattan distance doesn't split up, but the
+    /// cost is still convex, so the cheapest y for some x can be found
+    /// by a binary search on the slope, and the cheapest x by another
+    /// binary search around that.
+    fn manhattan_search(&self, cost: &FuelCost) -> (f64, f64) {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds().unwrap();
+
+        let best_y = |x: isize| {
+            let (mut lo, mut hi) = (min_y, max_y);
+            while lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                if self.manhattan_cost(cost, x, mid + 1) < self.manhattan_cost(cost, x, mid) {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+
+            (lo, self.manhattan_cost(cost, x, lo))
+        };
+
+        let (mut lo, mut hi) = (min_x, max_x);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if best_y(mid + 1).1 < best_y(mid).1 {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        (lo as f64, best_y(lo).0 as f64)
+    }
+
+    /// Weiszfeld's algorithm, generalised to other costs: every step
+    /// moves to the average of the crabs, each weighted by how steep its
+    /// cost is over how far away it is. Starts from the plain average.
+    fn weiszfeld(&self, cost: &FuelCost) -> (f64, f64) {
+        let weight = self.crabs.iter().map(|c| c.weight as f64).sum::<f64>();
+        let mut at = self.crabs.iter().fold((0.0, 0.0), |(x, y), c| {
+            let w = c.weight as f64 / weight;
+            (x + c.position.0 as f64 * w, y + c.position.1 as f64 * w)
+        });
+
+        for _ in 0..10_000 {
+            let (mut x, mut y, mut total) = (0.0, 0.0, 0.0);
+
+            for c in self.crabs.iter().filter(|c| c.weight > 0) {
+                let p = (c.position.0 as f64, c.position.1 as f64);
+                // sitting right on a crab would divide by zero
+                let d = Self::distance(Metric::Euclidean, at, p).max(1e-12);
+                let w = c.weight as f64 * cost.slope_real(d) / d;
+
+                x += p.0 * w;
+                y += p.1 * w;
+                total += w;
+            }
+
+            if total == 0.0 {
+                break;
+            }
+
+            let next = (x / total, y / total);
+            let moved = Self::distance(Metric::Euclidean, at, next);
+            at = next;
+
+            if moved < 1e-10 {
+                break;
+            }
+        }
+
+        at
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::Rng;
     use std::fs::File;
 
     #[test]
@@ -87,6 +620,70 @@ mod tests {
         let mut crabs = CrabSubmarines::new(vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
         println!("{}", crabs.get_efficient_crabs());
         println!("{:?}", crabs.get_all_possible_vals());
+
+        assert_eq!(crabs.get_efficient_crabs(), 37);
+        assert_eq!(crabs.get_all_possible_vals(), (5, 168));
+    }
+
+    #[test]
+    fn cost_models() {
+        let crabs = CrabSubmarines::new(vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
+        let models = [
+            FuelCost::Linear,
+            FuelCost::Triangular,
+            FuelCost::Quadratic,
+            FuelCost::Custom("cubic".to_string(), Box::new(|d| d * d * d)),
+            FuelCost::Custom("flat".to_string(), Box::new(|_| 1)),
+        ];
+
+        for cost in models.iter() {
+            let alignment = crabs.optimise(cost).unwrap();
+            println!("{}: {:?}", cost.name(), alignment);
+            assert_eq!(Some(alignment), crabs.optimise_exhaustive(cost));
+        }
+
+        assert_eq!(
+            crabs.optimise(&FuelCost::Linear).unwrap().positions,
+            vec![2]
+        );
+        assert_eq!(crabs.optimise(&FuelCost::Quadratic).unwrap().cost, 291);
+
+        // every position from the leftmost to the rightmost crab ties
+        let flat = FuelCost::Custom("flat".to_string(), Box::new(|_| 1));
+        assert_eq!(
+            crabs.optimise(&flat).unwrap().positions,
+            (0..=16).collect::<Vec<usize>>()
+        );
+
+        // an even amount of crabs ties along the whole median
+        let crabs = CrabSubmarines::new(vec![1, 1, 5, 5]);
+        let alignment = crabs.optimise(&FuelCost::Linear).unwrap();
+        assert!(alignment.is_tied());
+        assert_eq!(alignment.positions, vec![1, 2, 3, 4, 5]);
+        assert_eq!(alignment.cost, 8);
+
+        let alignment = crabs.optimise(&FuelCost::Quadratic).unwrap();
+        assert_eq!(alignment.position(), 3);
+        assert!(!alignment.is_tied());
+
+        assert!(CrabSubmarines::new(Vec::new())
+            .optimise(&FuelCost::Linear)
+            .is_none());
+    }
+
+    #[test]
+    fn optimise_matches_exhaustive() {
+        let mut rng = Rng::new(7);
+        let models = [FuelCost::Linear, FuelCost::Triangular, FuelCost::Quadratic];
+
+        for _ in 0..200 {
+            let n = rng.gen_range(20) + 1;
+            let crabs = CrabSubmarines::new((0..n).map(|_| rng.gen_range(50)).collect());
+
+            for cost in models.iter() {
+                assert_eq!(crabs.optimise(cost), crabs.optimise_exhaustive(cost));
+            }
+        }
     }
 
     #[test]
@@ -96,7 +693,196 @@ mod tests {
         let mut crabs = CrabSubmarines::new_from_file(&mut file);
         println!("{:?}", crabs.crab_pos);
 
-        crabs.get_all_possible_vals();
-        // println!("{}", crabs.get_efficient_crabs());
+        println!("{:?}", crabs.get_all_possible_vals());
+        println!("{}", crabs.get_efficient_crabs());
+    }
+
+    #[test]
+    fn cost_curves() {
+        let crabs = CrabSubmarines::new(vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
+        let models = [
+            FuelCost::Linear,
+            FuelCost::Triangular,
+            FuelCost::Quadratic,
+            FuelCost::Custom("cubic".to_string(), Box::new(|d| d * d * d)),
+        ];
+
+        for cost in models.iter() {
+            let curve = crabs.cost_curve(cost).collect::<Vec<(usize, usize)>>();
+            assert_eq!(curve.len(), 17);
+
+            for (p, c) in curve {
+                assert_eq!(c, crabs.total_cost(cost, p));
+            }
+
+            println!("{}", crabs.plot_curve(cost, 17, 8));
+        }
+
+        let csv = crabs.curve_to_csv(&FuelCost::Triangular);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("position,triangular"));
+        assert_eq!(lines.nth(2), Some("2,206"));
+        assert_eq!(lines.nth(2), Some("5,168"));
+
+        let plot = crabs.plot_curve(&FuelCost::Linear, 17, 4);
+        let rows = plot.lines().collect::<Vec<&str>>();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], "                *");
+        // the optimum is always on the bottom row
+        assert_eq!(rows[3].chars().nth(2), Some('*'));
+
+        // squashed down, each column is the cheapest of its positions
+        let plot = crabs.plot_curve(&FuelCost::Linear, 6, 3);
+        assert!(plot.lines().all(|l| l.len() <= 6));
+
+        let mut rng = Rng::new(11);
+        for _ in 0..100 {
+            let n = rng.gen_range(20) + 1;
+            let crabs = CrabSubmarines::new((0..n).map(|_| rng.gen_range(40) + 3).collect());
+
+            for cost in models.iter().take(3) {
+                for (p, c) in crabs.cost_curve(cost) {
+                    assert_eq!(c, crabs.total_cost(cost, p));
+                }
+            }
+        }
+
+        let crabs = CrabSubmarines::new(vec![3]);
+        assert_eq!(
+            crabs.cost_curve(&FuelCost::Quadratic).collect::<Vec<_>>(),
+            vec![(3, 0)]
+        );
+        let nobody = CrabSubmarines::new(Vec::new());
+        assert_eq!(nobody.cost_curve(&FuelCost::Linear).count(), 0);
+        assert_eq!(nobody.plot_curve(&FuelCost::Linear, 10, 2), "\n\n");
+    }
+
+    fn planar_crabs(positions: &[(isize, isize, usize)]) -> PlanarCrabs {
+        PlanarCrabs::new(
+            positions
+                .iter()
+                .map(|(x, y, w)| PlanarCrab {
+                    position: (*x, *y),
+                    weight: *w,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn planar_parse() {
+        let crabs = PlanarCrabs::parse("0,0\n 3,-4 \n\n1,1,5\n").unwrap();
+        assert_eq!(crabs.crabs.len(), 3);
+        assert_eq!(crabs.crabs[1].position, (3, -4));
+        assert_eq!(crabs.crabs[2].weight, 5);
+
+        assert!(PlanarCrabs::parse("1").is_err());
+        assert!(PlanarCrabs::parse("1,2,3,4").is_err());
+        assert!(PlanarCrabs::parse("1,a").is_err());
+        assert!(PlanarCrabs::parse("1,2,-1").is_err());
+        assert!(PlanarCrabs::parse("")
+            .unwrap()
+            .optimise(Metric::Euclidean, &FuelCost::Linear)
+            .is_none());
+    }
+
+    #[test]
+    fn planar_matches_line() {
+        // crabs on a line should come out the same as in one dimension
+        let line = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        let crabs = planar_crabs(&line.iter().map(|x| (*x, 0, 1)).collect::<Vec<_>>());
+
+        let manhattan = crabs
+            .optimise(Metric::Manhattan, &FuelCost::Linear)
+            .unwrap();
+        assert_eq!((manhattan.x, manhattan.y, manhattan.cost), (2.0, 0.0, 37.0));
+
+        let manhattan = crabs
+            .optimise(Metric::Manhattan, &FuelCost::Triangular)
+            .unwrap();
+        assert_eq!(
+            (manhattan.x, manhattan.y, manhattan.cost),
+            (5.0, 0.0, 168.0)
+        );
+
+        let euclidean = crabs
+            .optimise(Metric::Euclidean, &FuelCost::Quadratic)
+            .unwrap();
+        assert!((euclidean.x - 4.9).abs() < 1e-9);
+        assert!((euclidean.cost - 290.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn planar_manhattan() {
+        let models = [
+            FuelCost::Linear,
+            FuelCost::Triangular,
+            FuelCost::Quadratic,
+            FuelCost::Custom("cubic".to_string(), Box::new(|d| d * d * d)),
+        ];
+        let mut rng = Rng::new(3);
+
+        for _ in 0..50 {
+            let n = rng.gen_range(8) + 1;
+            let crabs = planar_crabs(
+                &(0..n)
+                    .map(|_| {
+                        let x = rng.gen_range(15) as isize - 5;
+                        let y = rng.gen_range(15) as isize - 5;
+                        (x, y, rng.gen_range(4) + 1)
+                    })
+                    .collect::<Vec<_>>(),
+            );
+
+            let ((min_x, min_y), (max_x, max_y)) = crabs.bounds().unwrap();
+            for cost in models.iter() {
+                let best = (min_x..=max_x)
+                    .flat_map(|x| (min_y..=max_y).map(move |y| (x, y)))
+                    .map(|(x, y)| crabs.manhattan_cost(cost, x, y))
+                    .min()
+                    .unwrap();
+
+                let alignment = crabs.optimise(Metric::Manhattan, cost).unwrap();
+                assert_eq!(alignment.cost, best as f64, "{}", cost.name());
+            }
+        }
+    }
+
+    #[test]
+    fn planar_euclidean() {
+        let crabs = planar_crabs(&[(0, 0, 1), (10, 0, 1), (0, 10, 1), (9, 9, 1)]);
+
+        for cost in [FuelCost::Linear, FuelCost::Triangular, FuelCost::Quadratic].iter() {
+            let a = crabs.optimise(Metric::Euclidean, cost).unwrap();
+            println!("{}: {:?}", cost.name(), a);
+
+            // nowhere close by is any cheaper
+            for (dx, dy) in [(1e-3, 0.0), (-1e-3, 0.0), (0.0, 1e-3), (0.0, -1e-3)].iter() {
+                let nearby = crabs.total_cost(Metric::Euclidean, cost, a.x + dx, a.y + dy);
+                assert!(nearby >= a.cost - 1e-9);
+            }
+        }
+
+        // quadratic cost is cheapest at the weighted average
+        let crabs = planar_crabs(&[(0, 0, 3), (10, 0, 1), (0, 10, 1)]);
+        let a = crabs
+            .optimise(Metric::Euclidean, &FuelCost::Quadratic)
+            .unwrap();
+        assert!((a.x - 2.0).abs() < 1e-9 && (a.y - 2.0).abs() < 1e-9);
+
+        // a crab that weighs more than everyone else gets visited
+        let crabs = planar_crabs(&[(0, 0, 1), (10, 0, 1), (0, 10, 1), (4, 3, 5)]);
+        for metric in [Metric::Manhattan, Metric::Euclidean].iter() {
+            let a = crabs.optimise(*metric, &FuelCost::Linear).unwrap();
+            assert!((a.x - 4.0).abs() < 1e-6 && (a.y - 3.0).abs() < 1e-6);
+        }
+
+        // custom costs are interpolated in between whole distances
+        let cubic = FuelCost::Custom("cubic".to_string(), Box::new(|d| d * d * d));
+        assert_eq!(cubic.cost_real(2.0), 8.0);
+        assert_eq!(cubic.cost_real(2.5), 17.5);
+        let a = crabs.optimise(Metric::Euclidean, &cubic).unwrap();
+        println!("{:?}", a);
+        assert!(a.cost <= crabs.total_cost(Metric::Euclidean, &cubic, 4.0, 3.0));
     }
 }
@@ -1,10 +1,73 @@
 use std::fs::File;
 use std::io::Read;
 
+use crate::util::BigUint;
+
+// past this many fish, individual fish aren't kept track of anymore
+const INSPECT_LIMIT: usize = 1 << 16;
+
+/// # Lifecycle
+///
+/// The rules a species of fish lives by. A fish at timer zero spawns
+/// `spawn_count` fish at `newborn`, and goes back to `reset` itself.
+/// With a `max_age`, fish die once they've been around that many days.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Lifecycle {
+    reset: usize,
+    newborn: usize,
+    spawn_count: usize,
+    max_age: Option<usize>,
+}
+
+impl Default for Lifecycle {
+    // the lanternfish from the puzzle
+    fn default() -> Self {
+        Lifecycle {
+            reset: 6,
+            newborn: 8,
+            spawn_count: 1,
+            max_age: None,
+        }
+    }
+}
+
+impl Lifecycle {
+    // how many ages have to be told apart, one if age doesn't matter
+    fn ages(&self) -> usize {
+        self.max_age.unwrap_or(1)
+    }
+
+    // like "reset=6 newborn=8", anything left out stays at the default
+    fn parse(rules: &str) -> Result<Self, String> {
+        let mut lifecycle = Lifecycle::default();
+
+        for rule in rules.split_whitespace() {
+            let (key, value) = rule
+                .split_once('=')
+                .ok_or(format!("rule {} has no value", rule))?;
+            let value = value
+                .parse::<usize>()
+                .map_err(|e| format!("rule {}: {}", rule, e))?;
+
+            match key {
+                "reset" => lifecycle.reset = value,
+                "newborn" => lifecycle.newborn = value,
+                "spawn" => lifecycle.spawn_count = value,
+                "max_age" if value == 0 => return Err("max_age must be non-zero".to_string()),
+                "max_age" => lifecycle.max_age = Some(value),
+                _ => return Err(format!("unknown rule {}", key)),
+            }
+        }
+
+        Ok(lifecycle)
+    }
+}
+
 #[derive(Debug, PartialOrd, Ord, PartialEq, Eq)]
 struct LanternFish {
     timer: usize,
     spawning: bool,
+    age: usize,
 }
 
 impl LanternFish {
@@ -12,6 +75,7 @@ impl LanternFish {
         LanternFish {
             timer,
             spawning: false,
+            age: 0,
         }
     }
 
@@ -32,13 +96,54 @@ impl LanternFish {
     }
 }
 
+/// # FishSimulation
+///
+/// Fish only differ by their timer (and their age, if they can die of
+/// old age), so the simulation just counts how many fish there are per
+/// timer and age. A day costs the same no matter how many fish there are.
+///
+/// While the population is small, every fish is also simulated on its
+/// own, so they can be looked at one by one. That stops once there are
+/// more than `INSPECT_LIMIT` of them.
 struct FishSimulation {
-    fish: Vec<LanternFish>,
+    rules: Lifecycle,
+    // amount of timer values, counts are indexed by age * timers + timer
+    timers: usize,
+    counts: Vec<u128>,
+    fish: Option<Vec<LanternFish>>,
+    day: usize,
 }
 
 impl FishSimulation {
     fn new(fish: Vec<LanternFish>) -> Self {
-        FishSimulation { fish }
+        Self::with_rules(Lifecycle::default(), fish)
+    }
+
+    fn with_rules(rules: Lifecycle, fish: Vec<LanternFish>) -> Self {
+        let timers = fish
+            .iter()
+            .map(|f| f.timer)
+            .chain([rules.reset, rules.newborn])
+            .max()
+            .unwrap()
+            + 1;
+
+        let mut counts = vec![0; timers * rules.ages()];
+        for f in fish.iter() {
+            counts[f.timer] += 1;
+        }
+
+        FishSimulation {
+            rules,
+            timers,
+            counts,
+            fish: if fish.len() <= INSPECT_LIMIT {
+                Some(fish)
+            } else {
+                None
+            },
+            day: 0,
+        }
     }
 
     fn new_from_file(file: File) -> Self {
@@ -54,39 +159,91 @@ impl FishSimulation {
         Self::new(fish)
     }
 
-    fn process(&mut self, days: usize) {
-        // store fish indexes here
-        let mut spawning_fish: usize = 0;
+    fn total(&self) -> u128 {
+        self.counts.iter().sum()
+    }
+
+    fn count(&self, timer: usize) -> u128 {
+        if timer >= self.timers {
+            return 0;
+        }
+
+        self.counts.iter().skip(timer).step_by(self.timers).sum()
+    }
 
+    /// Every fish, if there's still few enough of them to keep around.
+    fn fish(&self) -> Option<&[LanternFish]> {
+        self.fish.as_deref()
+    }
+
+    fn process(&mut self, days: usize) {
         for _ in 0..days {
-            // println!("{:?}", self.fish.len());
-            /*
-            for f in self.fish.iter_mut().enumerate() {
-                if f.1.timer == 0 && f.1.spawning {
-                    spawning_fish.push(f.0);
-                    f.1.timer = 6;
-                } else {
-                    f.1.dec();
-                }
+            self.step();
+        }
+    }
+
+    // where the fish in counts[i] end up after a day, and how many fish
+    // each of them turns into there
+    fn successors(&self, i: usize) -> Vec<(usize, u128)> {
+        let rules = &self.rules;
+        let (age, timer) = (i / self.timers, i % self.timers);
+        let mut next = Vec::with_capacity(2);
+
+        let timer = if timer == 0 {
+            // newborns always start out at age zero
+            next.push((rules.newborn, rules.spawn_count as u128));
+            rules.reset
+        } else {
+            timer - 1
+        };
+
+        match rules.max_age {
+            Some(max) if age + 1 >= max => (),
+            Some(_) => next.push(((age + 1) * self.timers + timer, 1)),
+            None => next.push((timer, 1)),
+        }
+
+        next
+    }
+
+    fn step(&mut self) {
+        let rules = &self.rules;
+        let mut next = vec![0u128; self.counts.len()];
+
+        for (i, n) in self.counts.iter().enumerate().filter(|(_, n)| **n > 0) {
+            for (j, m) in self.successors(i) {
+                let n = n.checked_mul(m).expect("fish population overflowed");
+                add_fish(&mut next[j], n);
             }
-            */
+        }
 
-            spawning_fish = Self::dec_recur(&mut self.fish);
+        self.counts = next;
 
-            for f in 0..spawning_fish {
-                self.fish.push(LanternFish::new(8));
+        if let Some(fish) = self.fish.as_mut() {
+            let spawned = Self::dec_recur(fish, rules) * rules.spawn_count;
+
+            if let Some(max) = rules.max_age {
+                fish.retain(|f| f.age < max);
             }
 
-            spawning_fish = 0;
+            if fish.len() + spawned > INSPECT_LIMIT {
+                self.fish = None;
+            } else {
+                fish.extend((0..spawned).map(|_| LanternFish::new(rules.newborn)));
+            }
         }
+
+        self.day += 1;
     }
 
-    fn dec_recur(fish: &mut [LanternFish]) -> usize {
+    fn dec_recur(fish: &mut [LanternFish], rules: &Lifecycle) -> usize {
         match fish.len() {
             0 => 0,
             1 => {
+                fish[0].age += 1;
+
                 if fish[0].timer == 0 {
-                    fish[0].timer = 6;
+                    fish[0].set(rules.reset);
                     1
                 } else {
                     fish[0].dec();
@@ -95,10 +252,343 @@ impl FishSimulation {
             }
             _ => {
                 let fish_len = fish.len();
-                Self::dec_recur(&mut fish[0..fish_len / 2])
-                    + Self::dec_recur(&mut fish[fish_len / 2..fish_len])
+                Self::dec_recur(&mut fish[0..fish_len / 2], rules)
+                    + Self::dec_recur(&mut fish[fish_len / 2..fish_len], rules)
+            }
+        }
+    }
+}
+
+// what the transition matrix needs from the numbers it holds
+trait Count: Clone {
+    fn add(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Modular {
+    n: u64,
+    modulus: u64,
+}
+
+impl Modular {
+    fn new(n: u128, modulus: u64) -> Self {
+        Modular {
+            n: (n % modulus as u128) as u64,
+            modulus,
+        }
+    }
+}
+
+impl Count for Modular {
+    fn add(&self, other: &Self) -> Self {
+        Modular::new(self.n as u128 + other.n as u128, self.modulus)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        Modular::new(self.n as u128 * other.n as u128, self.modulus)
+    }
+}
+
+impl Count for BigUint {
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+}
+
+type Matrix<T> = Vec<Vec<T>>;
+
+fn mat_mul<T: Count>(a: &Matrix<T>, b: &Matrix<T>, zero: &T) -> Matrix<T> {
+    (0..a.len())
+        .map(|i| {
+            (0..b[0].len())
+                .map(|j| (0..b.len()).fold(zero.clone(), |sum, k| sum.add(&a[i][k].mul(&b[k][j]))))
+                .collect()
+        })
+        .collect()
+}
+
+// square and multiply
+fn mat_pow<T: Count>(mut m: Matrix<T>, mut n: u64, zero: &T, one: &T) -> Matrix<T> {
+    let mut result = (0..m.len())
+        .map(|i| {
+            (0..m.len())
+                .map(|j| if i == j { one.clone() } else { zero.clone() })
+                .collect()
+        })
+        .collect::<Matrix<T>>();
+
+    while n > 0 {
+        if n & 1 == 1 {
+            result = mat_mul(&result, &m, zero);
+        }
+
+        m = mat_mul(&m, &m, zero);
+        n >>= 1;
+    }
+
+    result
+}
+
+/// Skipping ahead without simulating every day: one day is a linear map
+/// on the counts, so `days` days are that map's matrix to the power of
+/// `days`, which only takes log(days) matrix multiplications.
+///
+/// The matrix is as wide as the amount of counts, so this gets slow for
+/// species with a large `max_age`.
+impl FishSimulation {
+    // matrix[j][i] is how many fish in counts[j] one fish in counts[i]
+    // turns into
+    fn transition<T: Count>(&self, lift: impl Fn(u128) -> T) -> Matrix<T> {
+        let size = self.counts.len();
+        let columns = (0..size)
+            .map(|i| {
+                let mut column = vec![0u128; size];
+                for (j, m) in self.successors(i) {
+                    column[j] += m;
+                }
+                column
+            })
+            .collect::<Vec<Vec<u128>>>();
+
+        (0..size)
+            .map(|j| columns.iter().map(|c| lift(c[j])).collect())
+            .collect()
+    }
+
+    fn total_after<T: Count>(&self, days: u64, lift: impl Fn(u128) -> T) -> T {
+        let zero = lift(0);
+        let matrix = mat_pow(self.transition(&lift), days, &zero, &lift(1));
+
+        matrix.iter().fold(zero.clone(), |total, row| {
+            row.iter()
+                .zip(self.counts.iter())
+                .fold(total, |total, (m, n)| total.add(&m.mul(&lift(*n))))
+        })
+    }
+
+    /// The population `days` days from now, modulo `modulus`.
+    fn total_after_mod(&self, days: u64, modulus: u64) -> u64 {
+        if modulus == 0 {
+            panic!("modulus must be non-zero");
+        }
+
+        self.total_after(days, |n| Modular::new(n, modulus)).n
+    }
+
+    /// The exact population `days` days from now. The population grows
+    /// exponentially, so the answer has about as many digits as there
+    /// are days - fine for thousands of days, hopeless for 10^12.
+    fn total_after_big(&self, days: u64) -> BigUint {
+        self.total_after(days, BigUint::from)
+    }
+}
+
+/// # History
+///
+/// The population of a simulation on every day it ran for, per timer
+/// value (fish of every age are added together).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct History {
+    start: usize,
+    days: Vec<Vec<u128>>,
+}
+
+impl History {
+    fn total(&self, day: usize) -> Option<u128> {
+        let counts = self.days.get(day.checked_sub(self.start)?)?;
+        Some(counts.iter().sum())
+    }
+
+    fn to_csv(&self) -> String {
+        let timers = self.days.first().map(|d| d.len()).unwrap_or(0);
+        let mut res = String::from("day,total");
+        for t in 0..timers {
+            res.push_str(&format!(",t{}", t));
+        }
+        res.push('\n');
+
+        for (i, counts) in self.days.iter().enumerate() {
+            res.push_str(&format!(
+                "{},{}",
+                self.start + i,
+                counts.iter().sum::<u128>()
+            ));
+            for n in counts {
+                res.push_str(&format!(",{}", n));
             }
+            res.push('\n');
         }
+
+        res
+    }
+}
+
+impl FishSimulation {
+    fn timer_counts(&self) -> Vec<u128> {
+        (0..self.timers).map(|t| self.count(t)).collect()
+    }
+
+    /// Runs for `days` days like `process`, keeping every day (including
+    /// the one it started on).
+    fn history(&mut self, days: usize) -> History {
+        let mut history = History {
+            start: self.day,
+            days: vec![self.timer_counts()],
+        };
+
+        for _ in 0..days {
+            self.step();
+            history.days.push(self.timer_counts());
+        }
+
+        history
+    }
+
+    // runs a copy of the counts (not the fish) until `found` is true,
+    // giving up after `limit` days
+    fn find_day(&self, limit: usize, mut found: impl FnMut(&Self) -> bool) -> Option<usize> {
+        let mut sim = FishSimulation {
+            rules: self.rules.clone(),
+            timers: self.timers,
+            counts: self.counts.clone(),
+            fish: None,
+            day: self.day,
+        };
+
+        for _ in 0..=limit {
+            if found(&sim) {
+                return Some(sim.day);
+            }
+
+            sim.step();
+        }
+
+        None
+    }
+
+    /// The first day (from today on) there are more than `threshold` fish.
+    fn first_day_above(&self, threshold: u128, limit: usize) -> Option<usize> {
+        self.find_day(limit, |s| s.total() > threshold)
+    }
+
+    /// The first day (from today on) there are no fish at `timer`.
+    fn first_day_empty(&self, timer: usize, limit: usize) -> Option<usize> {
+        self.find_day(limit, |s| s.count(timer) == 0)
+    }
+
+    /// How much the population grows by per day in the long run, and
+    /// the day the daily growth stopped changing by more than `tolerance`.
+    /// It has to stay that way for as many days as there are timer values,
+    /// otherwise the quiet days before the first spawns would count.
+    /// None if it didn't settle within `limit` days, or everything died.
+    ///
+    /// Runs on floats scaled back down every day, so this never overflows.
+    fn growth_rate(&self, tolerance: f64, limit: usize) -> Option<(usize, f64)> {
+        let mut counts = self.counts.iter().map(|n| *n as f64).collect::<Vec<f64>>();
+        let mut last_rate: Option<f64> = None;
+        let mut stable = 0;
+
+        for day in self.day + 1..=self.day + limit {
+            let before = counts.iter().sum::<f64>();
+            let mut next = vec![0.0; counts.len()];
+
+            for (i, n) in counts.iter().enumerate() {
+                for (j, m) in self.successors(i) {
+                    next[j] += n * m as f64;
+                }
+            }
+
+            let after = next.iter().sum::<f64>();
+            if before == 0.0 || after == 0.0 {
+                return None;
+            }
+
+            let rate = after / before;
+            if last_rate.is_some_and(|r| (rate - r).abs() < tolerance) {
+                stable += 1;
+            } else {
+                stable = 0;
+            }
+
+            if stable >= self.timers {
+                return Some((day - stable, rate));
+            }
+
+            last_rate = Some(rate);
+            counts = next.iter().map(|n| n / after).collect();
+        }
+
+        None
+    }
+}
+
+fn add_fish(count: &mut u128, n: u128) {
+    *count = count.checked_add(n).expect("fish population overflowed");
+}
+
+/// # Ecosystem
+///
+/// Several species of fish, each with their own rules, simulated side
+/// by side. They don't affect each other.
+struct Ecosystem {
+    species: Vec<(String, FishSimulation)>,
+}
+
+impl Ecosystem {
+    /// One species per line, as `name rule=value ...: timers`, e.g.
+    /// `lanternfish reset=6 newborn=8 spawn=1: 3,4,3,1,2`. A line without
+    /// a name is just the puzzle input, and is read as lanternfish.
+    fn parse(input: &str) -> Result<Self, String> {
+        let mut species = Vec::new();
+
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (header, timers) = line.split_once(':').unwrap_or(("lanternfish", line));
+            let (name, rules) = header.trim().split_once(' ').unwrap_or((header.trim(), ""));
+
+            if name.is_empty() {
+                return Err(format!("species in \"{}\" has no name", line));
+            }
+
+            let rules = Lifecycle::parse(rules).map_err(|e| format!("{}: {}", name, e))?;
+            let fish = timers
+                .split(',')
+                .map(|t| t.trim().parse::<usize>().map(LanternFish::new))
+                .collect::<Result<Vec<LanternFish>, _>>()
+                .map_err(|e| format!("{}: {}", name, e))?;
+
+            species.push((name.to_string(), FishSimulation::with_rules(rules, fish)));
+        }
+
+        Ok(Ecosystem { species })
+    }
+
+    fn from_file(mut file: File) -> Result<Self, String> {
+        let mut input = String::new();
+        file.read_to_string(&mut input).map_err(|e| e.to_string())?;
+
+        Self::parse(&input)
+    }
+
+    fn process(&mut self, days: usize) {
+        for (_, sim) in self.species.iter_mut() {
+            sim.process(days);
+        }
+    }
+
+    fn total(&self) -> u128 {
+        self.species.iter().map(|(_, s)| s.total()).sum()
+    }
+
+    fn totals(&self) -> Vec<(&str, u128)> {
+        self.species
+            .iter()
+            .map(|(name, s)| (name.as_str(), s.total()))
+            .collect()
     }
 }
 
@@ -107,13 +597,26 @@ mod tests {
     use super::*;
     use std::fs::File;
 
+    fn example() -> FishSimulation {
+        FishSimulation::new(vec![
+            LanternFish::new(3),
+            LanternFish::new(4),
+            LanternFish::new(3),
+            LanternFish::new(1),
+            LanternFish::new(2),
+        ])
+    }
+
     #[test]
     fn test_day_six() {
         let file = File::open("res/day_six.input").unwrap();
         let mut sim = FishSimulation::new_from_file(file);
 
         sim.process(80);
-        println!("{}", sim.fish.len());
+        println!("{}", sim.total());
+
+        sim.process(256 - 80);
+        println!("{}", sim.total());
     }
 
     #[test]
@@ -126,22 +629,232 @@ mod tests {
             LanternFish::new(2),
         ];
 
-        FishSimulation::dec_recur(&mut fish);
+        FishSimulation::dec_recur(&mut fish, &Lifecycle::default());
         println!("{:?}", fish);
     }
 
     #[test]
     fn fish_test() {
-        let mut sim = FishSimulation::new(vec![
-            LanternFish::new(3),
-            LanternFish::new(4),
-            LanternFish::new(3),
-            LanternFish::new(1),
-            LanternFish::new(2),
-        ]);
+        let mut sim = example();
+
+        sim.process(18);
+        assert_eq!(sim.total(), 26);
+
+        sim.process(80 - 18);
+        assert_eq!(sim.total(), 5934);
+
+        sim.process(256 - 80);
+        println!("{}", sim.total());
+        assert_eq!(sim.total(), 26984457539);
+    }
+
+    #[test]
+    fn inspect_small_populations() {
+        let mut sim = example();
+
+        for _ in 0..40 {
+            sim.step();
+
+            let fish = sim.fish().unwrap();
+            assert_eq!(fish.len() as u128, sim.total());
+            for t in 0..=8 {
+                let n = fish.iter().filter(|f| f.timer == t).count();
+                assert_eq!(n as u128, sim.count(t));
+            }
+        }
+
+        // way past the limit, only the counts are left
+        sim.process(200);
+        assert!(sim.fish().is_none());
+        assert_eq!(sim.day, 240);
+    }
+
+    #[test]
+    fn huge_populations() {
+        let mut sim = example();
+        sim.process(900);
+
+        println!("{}", sim.total());
+        assert!(sim.total() > u64::MAX as u128);
+    }
+
+    #[test]
+    fn lifecycle_rules() {
+        assert_eq!(Lifecycle::parse("").unwrap(), Lifecycle::default());
+        assert_eq!(
+            Lifecycle::parse("reset=2 spawn=3 max_age=10").unwrap(),
+            Lifecycle {
+                reset: 2,
+                newborn: 8,
+                spawn_count: 3,
+                max_age: Some(10),
+            }
+        );
+        assert!(Lifecycle::parse("reset").is_err());
+        assert!(Lifecycle::parse("reset=a").is_err());
+        assert!(Lifecycle::parse("speed=2").is_err());
+        assert!(Lifecycle::parse("max_age=0").is_err());
+    }
+
+    #[test]
+    fn custom_lifecycles() {
+        // every fish makes two more every other day
+        let rules = Lifecycle::parse("reset=1 newborn=1 spawn=2").unwrap();
+        let mut sim = FishSimulation::with_rules(rules, vec![LanternFish::new(0)]);
+        let totals = (0..5)
+            .map(|_| {
+                sim.step();
+                sim.total()
+            })
+            .collect::<Vec<u128>>();
+        assert_eq!(totals, vec![3, 3, 9, 9, 27]);
+
+        // fish that spawn once and then die off
+        let rules = Lifecycle::parse("reset=3 newborn=3 max_age=4").unwrap();
+        let mut sim = FishSimulation::with_rules(rules, vec![LanternFish::new(3)]);
+        let totals = (0..12)
+            .map(|_| {
+                sim.step();
+                sim.total()
+            })
+            .collect::<Vec<u128>>();
+        assert_eq!(totals, vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]);
+
+        // the per-fish simulation has to agree with the counts
+        let rules = Lifecycle::parse("reset=4 newborn=6 spawn=2 max_age=12").unwrap();
+        let fish = [3, 4, 3, 1, 2]
+            .iter()
+            .map(|t| LanternFish::new(*t))
+            .collect();
+        let mut sim = FishSimulation::with_rules(rules, fish);
+        for _ in 0..40 {
+            sim.step();
+
+            let fish = sim.fish().unwrap();
+            assert_eq!(fish.len() as u128, sim.total());
+            for t in 0..sim.timers {
+                let n = fish.iter().filter(|f| f.timer == t).count();
+                assert_eq!(n as u128, sim.count(t));
+            }
+        }
+    }
+
+    #[test]
+    fn ecosystem() {
+        let mut eco = Ecosystem::parse(
+            "3,4,3,1,2\n\
+             fast reset=2 newborn=3: 1, 2\n\
+             mayfly newborn=0 max_age=1: 0,0,0\n",
+        )
+        .unwrap();
+
+        eco.process(80);
+        let totals = eco.totals();
+        println!("{:?}", totals);
+
+        assert_eq!(totals[0], ("lanternfish", 5934));
+        assert_eq!(totals[1].0, "fast");
+        // every mayfly replaces itself on the day it dies
+        assert_eq!(totals[2], ("mayfly", 3));
+        assert_eq!(eco.total(), totals.iter().map(|t| t.1).sum::<u128>());
+
+        assert!(Ecosystem::parse("fast reset=x: 1").is_err());
+        assert!(Ecosystem::parse("fast: 1,b").is_err());
+        assert!(Ecosystem::parse(": 1").is_err());
+
+        let file = File::open("res/day_six.input").unwrap();
+        let mut eco = Ecosystem::from_file(file).unwrap();
+        let mut sim = FishSimulation::new_from_file(File::open("res/day_six.input").unwrap());
+        eco.process(80);
+        sim.process(80);
+        assert_eq!(eco.total(), sim.total());
+    }
+
+    #[test]
+    fn matrix_skip_ahead() {
+        const PRIME: u64 = 1_000_000_007;
+
+        let start = example();
+        assert_eq!(start.total_after_mod(256, u64::MAX), 26984457539);
+        assert_eq!(start.total_after_big(256).to_u128(), Some(26984457539));
+
+        // has to agree with just simulating it
+        let mut sim = example();
+        for day in 0..300 {
+            let total = sim.total();
+
+            assert_eq!(
+                start.total_after_mod(day, PRIME) as u128,
+                total % PRIME as u128
+            );
+            assert_eq!(start.total_after_big(day).to_u128(), Some(total));
+            sim.step();
+        }
+
+        // and with other rules too
+        let rules = Lifecycle::parse("reset=4 newborn=6 spawn=2 max_age=5").unwrap();
+        let fish = || vec![LanternFish::new(3), LanternFish::new(1)];
+        let start = FishSimulation::with_rules(rules.clone(), fish());
+        let mut sim = FishSimulation::with_rules(rules, fish());
+        for day in 0..40 {
+            assert_eq!(start.total_after_mod(day, 97) as u128, sim.total() % 97);
+            sim.step();
+        }
+        assert_eq!(start.total_after_big(40).to_u128(), Some(sim.total()));
+
+        let sim = example();
+        println!("{}", sim.total_after_mod(1_000_000_000_000, PRIME));
+        assert_eq!(sim.total_after_mod(1_000_000_000_000, 1), 0);
+
+        // well past what fits in a u128
+        let big = sim.total_after_big(2000);
+        println!("{}", big);
+        assert_eq!(big.to_u128(), None);
+
+        let low_digits = format!("{:09}", sim.total_after_mod(2000, 1_000_000_000));
+        assert!(big.to_string().ends_with(&low_digits));
+    }
+
+    #[test]
+    fn history_and_queries() {
+        let mut sim = example();
+        let history = sim.history(18);
+        assert_eq!(sim.day, 18);
+        assert_eq!(history.total(0), Some(5));
+        assert_eq!(history.total(18), Some(26));
+        assert_eq!(history.total(19), None);
+
+        let csv = history.to_csv();
+        println!("{}", csv);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("day,total,t0,t1,t2,t3,t4,t5,t6,t7,t8"));
+        assert_eq!(lines.next(), Some("0,5,0,1,1,2,1,0,0,0,0"));
+        assert_eq!(lines.next(), Some("1,5,1,1,2,1,0,0,0,0,0"));
+        assert_eq!(lines.next(), Some("2,6,1,2,1,0,0,0,1,0,1"));
+        assert_eq!(lines.count(), 16);
+
+        // carries on from where the last one stopped
+        assert_eq!(sim.history(2).start, 18);
+
+        let sim = example();
+        assert_eq!(sim.first_day_above(5933, 100), Some(80));
+        assert_eq!(sim.first_day_above(26984457538, 300), Some(256));
+        assert_eq!(sim.first_day_above(26984457539, 100), None);
+        assert_eq!(sim.first_day_above(0, 0), Some(0));
+
+        assert_eq!(sim.first_day_empty(0, 10), Some(0));
+        assert_eq!(sim.first_day_empty(3, 10), Some(2));
+        assert_eq!(sim.first_day_empty(8, 10), Some(0));
 
-        sim.process(256);
+        // the long run growth is the root of x^9 = x^2 + 1
+        let (day, rate) = sim.growth_rate(1e-9, 5000).unwrap();
+        println!("{} {}", day, rate);
+        assert!((rate.powi(9) - rate.powi(2) - 1.0).abs() < 1e-6);
+        assert!(day > 18);
+        assert!(sim.growth_rate(1e-9, day).is_none());
 
-        println!("{}", sim.fish.len());
+        let rules = Lifecycle::parse("newborn=0 spawn=0 max_age=3").unwrap();
+        let sim = FishSimulation::with_rules(rules, vec![LanternFish::new(1)]);
+        assert_eq!(sim.growth_rate(1e-9, 100), None);
     }
 }
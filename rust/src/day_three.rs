@@ -1,153 +1,284 @@
+use crate::util::BitVec;
 use std::cmp::Ordering;
-use std::iter::Iterator;
 
-struct BitIter {
-    num: usize,
-    cur_bit: u32,
+#[derive(Debug)]
+struct BitCount(pub usize, pub usize); // 0 is 0s, 1 is 1s
+
+/// # TiePolicy
+///
+/// What to do with a column that has as many 0s as 1s when working out
+/// the most common bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TiePolicy {
+    PreferZero,
+    PreferOne,
+    Error,
 }
 
-impl Iterator for BitIter {
-    type Item = u8;
+impl TiePolicy {
+    fn name(&self) -> &str {
+        match self {
+            TiePolicy::PreferZero => "prefer_zero",
+            TiePolicy::PreferOne => "prefer_one",
+            TiePolicy::Error => "error",
+        }
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.cur_bit == usize::BITS {
-            None
-        } else {
-            let res = Some(get_nth_bit_in_word(self.num, self.cur_bit as usize));
-            self.cur_bit += 1;
+impl BitCount {
+    fn is_tied(&self) -> bool {
+        self.0 == self.1
+    }
 
-            res
+    fn get_common_bits(count_vec: &[Self], policy: TiePolicy) -> Result<BitVec, RatingError> {
+        let mut res = BitVec::new(count_vec.len());
+        for i in count_vec.iter().enumerate() {
+            let tie = match policy {
+                TiePolicy::PreferZero => false,
+                TiePolicy::PreferOne => true,
+                TiePolicy::Error if i.1.is_tied() => return Err(RatingError::Tied(i.0)),
+                TiePolicy::Error => false,
+            };
+
+            res.set(i.0, BitCriterion::MostCommon.keep_bit(i.1 .0, i.1 .1, tie));
         }
+
+        Ok(res)
     }
-}
 
-impl BitIter {
-    fn new(num: usize) -> Self {
-        BitIter { num, cur_bit: 0 }
+    // the least common bits are always the complement of the most common
+    // ones, ties included
+    fn get_least_common_bits(count_vec: &[Self], policy: TiePolicy) -> Result<BitVec, RatingError> {
+        Self::get_common_bits(count_vec, policy).map(|b| b.invert())
     }
 }
 
+/// # DiagnosticReport
+///
+/// Per-column bit counts for a set of words, and the gamma/epsilon
+/// rates they work out to.
 #[derive(Debug)]
-struct BitCount(pub usize, pub usize); // 0 is 0s, 1 is 1s
+struct DiagnosticReport {
+    columns: Vec<BitCount>,
+    policy: TiePolicy,
+    gamma: BitVec,
+    epsilon: BitVec,
+}
 
-impl BitCount {
-    fn add_zero(&mut self) {
-        self.0 += 1;
+impl DiagnosticReport {
+    fn new(word_vec: &[BitVec], policy: TiePolicy) -> Result<Self, RatingError> {
+        let columns = count_bits_per_field(word_vec);
+        let gamma = BitCount::get_common_bits(&columns, policy)?;
+        let epsilon = gamma.invert();
+
+        Ok(DiagnosticReport {
+            columns,
+            policy,
+            gamma,
+            epsilon,
+        })
     }
 
-    fn add_one(&mut self) {
-        self.1 += 1;
+    // None if either rate is too wide to multiply out
+    fn power(&self) -> Option<u128> {
+        self.gamma.to_u128()?.checked_mul(self.epsilon.to_u128()?)
     }
 
-    fn get_common_bits(count_vec: &[Self]) -> usize {
-        let mut cur_bit = count_vec.len();
-        let mut res = 0;
-        for i in count_vec {
-            let new_bit = if i.0 < i.1 { 1 } else { 0 };
-            res |= new_bit << (cur_bit - 1);
-            cur_bit -= 1;
+    fn to_table(&self) -> String {
+        let mut res = String::from("column | zeros | ones | tied\n");
+        for c in self.columns.iter().enumerate() {
+            res.push_str(&format!(
+                "{:>6} | {:>5} | {:>4} | {}\n",
+                c.0,
+                c.1 .0,
+                c.1 .1,
+                if c.1.is_tied() { "yes" } else { "no" }
+            ));
         }
 
+        let value = |v: Option<u128>| v.map_or("-".to_string(), |v| v.to_string());
+        res.push_str(&format!("ties: {}\n", self.policy.name()));
+        res.push_str(&format!(
+            "gamma: {} ({})\n",
+            self.gamma,
+            value(self.gamma.to_u128())
+        ));
+        res.push_str(&format!(
+            "epsilon: {} ({})\n",
+            self.epsilon,
+            value(self.epsilon.to_u128())
+        ));
+        res.push_str(&format!("power: {}\n", value(self.power())));
+
         res
     }
 
-    fn get_least_common_bits(count_vec: &[Self]) -> usize {
-        let shift = usize::BITS - count_vec.len() as u32;
-        (!Self::get_common_bits(count_vec) << shift) >> shift
+    fn to_json(&self) -> String {
+        let value = |v: Option<u128>| v.map_or("null".to_string(), |v| v.to_string());
+        let columns = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|c| {
+                format!(
+                    "{{\"column\":{},\"zeros\":{},\"ones\":{},\"tied\":{}}}",
+                    c.0,
+                    c.1 .0,
+                    c.1 .1,
+                    c.1.is_tied()
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(",");
+
+        format!(
+            "{{\"columns\":[{}],\"tie_policy\":\"{}\",\"gamma\":\"{}\",\"gamma_value\":{},\"epsilon\":\"{}\",\"epsilon_value\":{},\"power\":{}}}",
+            columns,
+            self.policy.name(),
+            self.gamma,
+            value(self.gamma.to_u128()),
+            self.epsilon,
+            value(self.epsilon.to_u128()),
+            value(self.power())
+        )
     }
 }
 
-fn get_nth_bit_in_word(word: usize, n: usize) -> u8 {
-    ((word >> n) & 1) as u8
-}
-
 // given some vector of words, count the amount of bits per
 // bit field L>R : i.e., counting 5 bits would count b4, b3, b2, b1, b0
 // in each word respectively, returning a vec of len 5
-fn count_bits_per_field(
-    word_vec: &[usize],
-    field_count: usize,
-    buf: &mut [BitCount], // caller must clear
-) {
-    for word in word_vec {
-        // clippy kept complaining about 0..field_count,
-        // so i just turned it into an iterator
-        for i in BitIter::new(*word).enumerate().take(field_count) {
-            match i.1 {
-                0 => buf[i.0].add_zero(),
-                1 => buf[i.0].add_one(),
-                _ => {
-                    panic!("bitmasking failed");
-                }
-            };
+fn count_bits_per_field(word_vec: &[BitVec]) -> Vec<BitCount> {
+    BitVec::count_columns(word_vec)
+        .into_iter()
+        .map(|c| BitCount(c.0, c.1))
+        .collect()
+}
+
+/// # BitCriterion
+///
+/// Which bit to keep when filtering a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BitCriterion {
+    MostCommon,
+    LeastCommon,
+}
+
+impl BitCriterion {
+    // on a tie, `tie` is the bit that gets kept
+    fn keep_bit(&self, zeros: usize, ones: usize, tie: bool) -> bool {
+        match (self, zeros.cmp(&ones)) {
+            (_, Ordering::Equal) => tie,
+            (BitCriterion::MostCommon, o) => o == Ordering::Less,
+            (BitCriterion::LeastCommon, o) => o == Ordering::Greater,
         }
     }
 }
 
-// this one is *really* ugly - there's probably a better way to do this
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RatingError {
+    NoWords,
+    // words have to all be the same width to go in the trie
+    WidthMismatch(usize, usize),
+    // nothing was left after filtering on this column - only an empty
+    // trie gets here
+    Emptied(usize),
+    // this column had as many 0s as 1s, and ties weren't allowed
+    Tied(usize),
+}
 
-// named against the challenge desc
-// because i don't know if this is a
-// mathematical property
-// word_vec are the words to use, field_count is the amount of bits to care about
-fn get_oxy_rating(mut word_vec: Vec<usize>, field_count: usize) -> usize {
-    let mut current_bit = field_count - 1;
-    let mut buf: Vec<BitCount> = Vec::new();
-    while word_vec.len() != 1 {
-        buf.clear();
-        buf.resize_with(field_count, || BitCount(0, 0));
-        let bit_count = count_bits_per_field(&word_vec, field_count, &mut buf);
-
-        let common_bit = match buf[current_bit].0.cmp(&buf[current_bit].1) {
-            Ordering::Less => 1,
-            Ordering::Greater => 0,
-            Ordering::Equal => 1,
+#[derive(Debug, Default)]
+struct TrieNode {
+    count: usize,
+    children: [Option<usize>; 2],
+}
+
+/// # BitTrie
+///
+/// Binary trie over a set of diagnostic words. Each node knows how many
+/// words pass through it, so the column counts for whatever's left after
+/// filtering are always just the counts on the two children.
+#[derive(Debug)]
+struct BitTrie {
+    nodes: Vec<TrieNode>,
+    width: usize,
+}
+
+impl BitTrie {
+    fn new(word_vec: &[BitVec]) -> Result<Self, RatingError> {
+        let width = word_vec.first().ok_or(RatingError::NoWords)?.len();
+        let mut trie = BitTrie {
+            nodes: vec![TrieNode::default()],
+            width,
         };
 
-        word_vec = word_vec
-            .into_iter()
-            .filter(|i| get_nth_bit_in_word(*i, current_bit) != common_bit)
-            .collect::<Vec<usize>>();
+        for word in word_vec {
+            if word.len() != width {
+                return Err(RatingError::WidthMismatch(width, word.len()));
+            }
 
-        // ??? why
-        if current_bit == 0 {
-            break;
+            trie.insert(word);
         }
 
-        current_bit -= 1;
+        Ok(trie)
     }
 
-    word_vec[0]
-}
+    fn insert(&mut self, word: &BitVec) {
+        let mut cur = 0;
+        self.nodes[cur].count += 1;
 
-fn get_co2_rating(mut word_vec: Vec<usize>, field_count: usize) -> usize {
-    let mut current_bit = field_count - 1;
-    let mut buf: Vec<BitCount> = Vec::new();
-    while word_vec.len() != 1 {
-        buf.clear();
-        buf.resize_with(field_count, || BitCount(0, 0));
-        let bit_count = count_bits_per_field(&word_vec, field_count, &mut buf);
-
-        let common_bit = match buf[current_bit].0.cmp(&buf[current_bit].1) {
-            Ordering::Less => 0,
-            Ordering::Greater => 1,
-            Ordering::Equal => 0,
-        };
+        for b in word.iter() {
+            cur = match self.nodes[cur].children[b as usize] {
+                Some(n) => n,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let n = self.nodes.len() - 1;
+                    self.nodes[cur].children[b as usize] = Some(n);
 
-        word_vec = word_vec
-            .into_iter()
-            .filter(|i| get_nth_bit_in_word(*i, current_bit) != common_bit)
-            .collect::<Vec<usize>>();
+                    n
+                }
+            };
 
-        // ??? why
-        if current_bit == 0 {
-            break;
+            self.nodes[cur].count += 1;
         }
+    }
 
-        current_bit -= 1;
+    fn count(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |n| self.nodes[n].count)
     }
 
-    word_vec[0]
+    // keeps filtering the words column by column, then returns whatever
+    // is left. a column where everything left has the same bit doesn't
+    // filter anything out, so once only one word (or duplicates of one)
+    // is left, that's what comes back
+    fn filter(&self, criterion: BitCriterion, tie: bool) -> Result<BitVec, RatingError> {
+        let mut res = BitVec::new(0);
+        let mut cur = 0;
+
+        for column in 0..self.width {
+            let [zero, one] = self.nodes[cur].children;
+
+            let bit = if zero.is_some() && one.is_some() {
+                criterion.keep_bit(self.count(zero), self.count(one), tie)
+            } else {
+                // only one way to go
+                one.is_some()
+            };
+
+            cur = self.nodes[cur].children[bit as usize].ok_or(RatingError::Emptied(column))?;
+            res.push(bit);
+        }
+
+        Ok(res)
+    }
+}
+
+// named against the challenge desc
+fn get_oxy_rating(trie: &BitTrie) -> Result<BitVec, RatingError> {
+    trie.filter(BitCriterion::MostCommon, true)
+}
+
+fn get_co2_rating(trie: &BitTrie) -> Result<BitVec, RatingError> {
+    trie.filter(BitCriterion::LeastCommon, false)
 }
 
 #[cfg(test)]
@@ -156,19 +287,39 @@ mod tests {
     use std::fs::File;
     use std::io::Read;
 
+    fn parse_words(s: &str) -> Vec<BitVec> {
+        s.lines().map(|i| i.parse::<BitVec>().unwrap()).collect()
+    }
+
     #[test]
     fn test_bit_counting() {
-        let mut buf: Vec<BitCount> = Vec::new();
-        buf.resize_with(12, || BitCount(0, 0));
-        count_bits_per_field(
-            &[usize::from_str_radix("110001010111", 2).unwrap()],
-            12,
-            &mut buf,
-        );
-        let mcb = BitCount::get_common_bits(&buf);
-        let lcb = BitCount::get_least_common_bits(&buf);
+        let buf = count_bits_per_field(&["110001010111".parse::<BitVec>().unwrap()]);
+        let mcb = BitCount::get_common_bits(&buf, TiePolicy::PreferZero).unwrap();
+        let lcb = BitCount::get_least_common_bits(&buf, TiePolicy::PreferZero).unwrap();
+
+        println!("{}, {}", mcb, lcb);
+        assert_eq!(mcb.to_string(), "110001010111");
+        assert_eq!(lcb.to_string(), "001110101000");
+    }
 
-        println!("{:12b}, {:12b}", mcb, lcb);
+    #[test]
+    fn test_example_power() {
+        let mut test_file = File::open("res/day_three.test").unwrap();
+        let mut test_string: String = String::new();
+
+        test_file.read_to_string(&mut test_string).unwrap();
+
+        let buf = count_bits_per_field(&parse_words(&test_string));
+        let mcb = BitCount::get_common_bits(&buf, TiePolicy::PreferZero)
+            .unwrap()
+            .to_usize()
+            .unwrap();
+        let lcb = BitCount::get_least_common_bits(&buf, TiePolicy::PreferZero)
+            .unwrap()
+            .to_usize()
+            .unwrap();
+
+        assert_eq!((mcb, lcb), (22, 9));
     }
 
     #[test]
@@ -183,27 +334,116 @@ mod tests {
 
         println!("field_count: {}", field_count);
 
-        let num_vec: Vec<usize> = test_string
-            .lines()
-            .map(|i| usize::from_str_radix(i, 2).unwrap())
-            .collect();
+        let num_vec = parse_words(&test_string);
 
-        let mut buf: Vec<BitCount> = Vec::new();
-        buf.resize_with(12, || BitCount(0, 0));
-        count_bits_per_field(&num_vec, field_count, &mut buf);
-        let mcb = BitCount::get_common_bits(&buf);
-        let lcb = BitCount::get_least_common_bits(&buf);
+        let buf = count_bits_per_field(&num_vec);
+        let mcb = BitCount::get_common_bits(&buf, TiePolicy::PreferZero).unwrap();
+        let lcb = BitCount::get_least_common_bits(&buf, TiePolicy::PreferZero).unwrap();
 
-        println!("mcb: {:12b}", mcb);
-        println!("lcb: {:12b}", lcb);
-        println!("mcb * lcb: {}", mcb * lcb);
+        println!("mcb: {}", mcb);
+        println!("lcb: {}", lcb);
+        println!(
+            "mcb * lcb: {}",
+            mcb.to_usize().unwrap() * lcb.to_usize().unwrap()
+        );
 
-        let oxy_rating = get_oxy_rating(num_vec.clone(), field_count);
-        let co2_rating = get_co2_rating(num_vec.clone(), field_count);
+        let trie = BitTrie::new(&num_vec).unwrap();
+        let oxy_rating = get_oxy_rating(&trie).unwrap().to_usize().unwrap();
+        let co2_rating = get_co2_rating(&trie).unwrap().to_usize().unwrap();
 
         println!("o2: {}", oxy_rating);
         println!("co2: {}", co2_rating);
 
         println!("{}", oxy_rating * co2_rating);
     }
+
+    #[test]
+    fn test_example_ratings() {
+        let mut test_file = File::open("res/day_three.test").unwrap();
+        let mut test_string: String = String::new();
+
+        test_file.read_to_string(&mut test_string).unwrap();
+
+        let trie = BitTrie::new(&parse_words(&test_string)).unwrap();
+
+        assert_eq!(get_oxy_rating(&trie).unwrap().to_usize(), Some(23));
+        assert_eq!(get_co2_rating(&trie).unwrap().to_usize(), Some(10));
+    }
+
+    #[test]
+    fn test_rating_errors() {
+        assert_eq!(BitTrie::new(&[]).unwrap_err(), RatingError::NoWords);
+        assert_eq!(
+            BitTrie::new(&parse_words("101\n11")).unwrap_err(),
+            RatingError::WidthMismatch(3, 2)
+        );
+
+        // everything shares the first bit, so that column keeps everything
+        let trie = BitTrie::new(&parse_words("101\n110\n111")).unwrap();
+        assert_eq!(
+            trie.filter(BitCriterion::LeastCommon, false)
+                .unwrap()
+                .to_string(),
+            "101"
+        );
+
+        // duplicates just end up as the single remaining word
+        let trie = BitTrie::new(&parse_words("011\n011\n100")).unwrap();
+        assert_eq!(
+            trie.filter(BitCriterion::MostCommon, true)
+                .unwrap()
+                .to_string(),
+            "011"
+        );
+        assert_eq!(
+            trie.filter(BitCriterion::LeastCommon, true)
+                .unwrap()
+                .to_string(),
+            "100"
+        );
+
+        // the least common side can end up holding only duplicates, which
+        // then have to be followed down the only child they've got
+        let trie = BitTrie::new(&parse_words("011\n011\n100\n100\n100")).unwrap();
+        assert_eq!(get_co2_rating(&trie).unwrap().to_string(), "011");
+        assert_eq!(get_oxy_rating(&trie).unwrap().to_string(), "100");
+    }
+
+    #[test]
+    fn test_diagnostic_report() {
+        let words = parse_words("110\n111\n100\n001");
+
+        // column 1 and 2 are both tied here
+        let report = DiagnosticReport::new(&words, TiePolicy::PreferOne).unwrap();
+        assert_eq!(report.gamma.to_string(), "111");
+        assert_eq!(report.epsilon.to_string(), "000");
+        assert_eq!(report.power(), Some(0));
+
+        let report = DiagnosticReport::new(&words, TiePolicy::PreferZero).unwrap();
+        assert_eq!(report.gamma.to_string(), "100");
+        assert_eq!(report.epsilon.to_string(), "011");
+
+        assert_eq!(
+            DiagnosticReport::new(&words, TiePolicy::Error).unwrap_err(),
+            RatingError::Tied(1)
+        );
+
+        let table = report.to_table();
+        println!("{}", table);
+        assert_eq!(table.lines().nth(1), Some("     0 |     1 |    3 | no"));
+        assert_eq!(table.lines().nth(2), Some("     1 |     2 |    2 | yes"));
+        assert!(table.contains("gamma: 100 (4)"));
+
+        assert_eq!(
+            report.to_json(),
+            "{\"columns\":[\
+             {\"column\":0,\"zeros\":1,\"ones\":3,\"tied\":false},\
+             {\"column\":1,\"zeros\":2,\"ones\":2,\"tied\":true},\
+             {\"column\":2,\"zeros\":2,\"ones\":2,\"tied\":true}],\
+             \"tie_policy\":\"prefer_zero\",\
+             \"gamma\":\"100\",\"gamma_value\":4,\
+             \"epsilon\":\"011\",\"epsilon_value\":3,\
+             \"power\":12}"
+        );
+    }
 }
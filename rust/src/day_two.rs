@@ -1,79 +1,373 @@
-#[derive(Debug)]
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 struct Position {
     horizontal: isize,
     depth: isize,
     aim: isize,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Command {
     Forward(usize),
     Down(usize),
     Up(usize),
+    Back(usize),
+    // turn the submarine around, so forward/back swap
+    Turn,
+    SetAim(isize),
 }
 
-impl Command {
-    pub fn parse(command: String) -> Result<Self, String> {
-        let action = command.split(' ').collect::<Vec<&str>>();
+/// # CommandError
+///
+/// Why a single command couldn't be parsed, with the offending token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CommandError {
+    Empty,
+    UnknownCommand(String),
+    MissingUnits(String),
+    // the command as written, and the token it still needed
+    MissingToken(String, String),
+    InvalidUnits(String),
+    UnexpectedToken(String),
+}
 
-        if action.len() != 2 {
-            return Err("too long".to_string());
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Empty => write!(f, "empty command"),
+            CommandError::UnknownCommand(t) => write!(f, "unknown command `{}`", t),
+            CommandError::MissingUnits(t) => write!(f, "`{}` is missing its units", t),
+            CommandError::MissingToken(t, m) => write!(f, "`{}` is missing `{}`", t, m),
+            CommandError::InvalidUnits(t) => write!(f, "invalid units `{}`", t),
+            CommandError::UnexpectedToken(t) => write!(f, "unexpected token `{}`", t),
         }
+    }
+}
+
+/// A `CommandError` along with the (1-indexed) line it happened on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LineError {
+    line: usize,
+    error: CommandError,
+}
 
-        let units: usize = action[1].parse().map_err(|_| "parse error".to_string())?;
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
 
-        match action[0] {
-            "forward" => Ok(Command::Forward(units)),
-            "down" => Ok(Command::Down(units)),
-            "up" => Ok(Command::Up(units)),
-            _ => Err("incorrect command".to_string()),
+impl FromStr for Command {
+    type Err = CommandError;
+
+    fn from_str(command: &str) -> Result<Self, Self::Err> {
+        let mut tokens = command.split_whitespace();
+        // matched case-insensitively, but errors show it as it was written
+        let name = tokens.next().ok_or(CommandError::Empty)?;
+
+        let cmd = match name.to_lowercase().as_str() {
+            "turn" => Command::Turn,
+            "set" => match tokens.next() {
+                Some(t) if t.eq_ignore_ascii_case("aim") => {
+                    Command::SetAim(Self::parse_units(&mut tokens, &format!("{} {}", name, t))?)
+                }
+                Some(t) => return Err(CommandError::UnexpectedToken(t.to_string())),
+                None => {
+                    return Err(CommandError::MissingToken(
+                        name.to_string(),
+                        "aim".to_string(),
+                    ))
+                }
+            },
+            "forward" => Command::Forward(Self::parse_units(&mut tokens, name)?),
+            "down" => Command::Down(Self::parse_units(&mut tokens, name)?),
+            "up" => Command::Up(Self::parse_units(&mut tokens, name)?),
+            "back" => Command::Back(Self::parse_units(&mut tokens, name)?),
+            _ => return Err(CommandError::UnknownCommand(name.to_string())),
+        };
+
+        match tokens.next() {
+            Some(t) => Err(CommandError::UnexpectedToken(t.to_string())),
+            None => Ok(cmd),
         }
     }
 }
 
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Forward(u) => write!(f, "forward {}", u),
+            Command::Down(u) => write!(f, "down {}", u),
+            Command::Up(u) => write!(f, "up {}", u),
+            Command::Back(u) => write!(f, "back {}", u),
+            Command::Turn => write!(f, "turn"),
+            Command::SetAim(a) => write!(f, "set aim {}", a),
+        }
+    }
+}
+
+impl Command {
+    fn parse_units<'a, T: FromStr>(
+        tokens: &mut impl Iterator<Item = &'a str>,
+        name: &str,
+    ) -> Result<T, CommandError> {
+        let t = tokens
+            .next()
+            .ok_or_else(|| CommandError::MissingUnits(name.to_string()))?;
+
+        t.parse()
+            .map_err(|_| CommandError::InvalidUnits(t.to_string()))
+    }
+}
+
+// parses a whole program, skipping blank lines - either every command
+// comes back, or every error does
+fn parse_program(program: &str) -> Result<Vec<Command>, Vec<LineError>> {
+    let mut commands = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, l) in program.lines().enumerate() {
+        if l.trim().is_empty() {
+            continue;
+        }
+
+        match l.parse::<Command>() {
+            Ok(c) => commands.push(c),
+            Err(error) => errors.push(LineError { line: i + 1, error }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(commands)
+    } else {
+        Err(errors)
+    }
+}
+
 impl Position {
     pub fn new() -> Self {
-        Position {
-            horizontal: 0,
-            depth: 0,
-            aim: 0,
+        Self::default()
+    }
+
+    pub fn product(&self) -> isize {
+        self.horizontal * self.depth
+    }
+}
+
+/// # MovementModel
+///
+/// An interpretation of the command stream. Models only have to react
+/// to the commands they care about - anything else can be ignored.
+trait MovementModel {
+    fn name(&self) -> &str;
+
+    fn apply(&mut self, command: &Command);
+
+    fn position(&self) -> &Position;
+}
+
+/// The naive reading: up/down move the depth directly.
+#[derive(Debug)]
+struct DirectModel {
+    pos: Position,
+    heading: isize,
+}
+
+impl DirectModel {
+    fn new() -> Self {
+        DirectModel {
+            pos: Position::new(),
+            heading: 1,
         }
     }
+}
+
+impl MovementModel for DirectModel {
+    fn name(&self) -> &str {
+        "direct"
+    }
 
-    pub fn change_pos(mut self, command: &Command) -> Self {
+    fn apply(&mut self, command: &Command) {
         match command {
-            Command::Forward(delta) => {
-                self.horizontal += *delta as isize;
-            }
+            Command::Forward(delta) => self.pos.horizontal += self.heading * *delta as isize,
+            Command::Back(delta) => self.pos.horizontal -= self.heading * *delta as isize,
+            Command::Down(delta) => self.pos.depth += *delta as isize,
+            Command::Up(delta) => self.pos.depth -= *delta as isize,
+            Command::Turn => self.heading = -self.heading,
+            Command::SetAim(_) => {}
+        }
+    }
 
-            Command::Down(delta) => {
-                self.depth += *delta as isize;
-            }
+    fn position(&self) -> &Position {
+        &self.pos
+    }
+}
 
-            Command::Up(delta) => {
-                self.depth -= *delta as isize;
-            }
+/// The manual's reading: up/down change the aim, and moving
+/// changes the depth by the aim.
+#[derive(Debug)]
+struct AimModel {
+    pos: Position,
+    heading: isize,
+}
+
+impl AimModel {
+    fn new() -> Self {
+        AimModel {
+            pos: Position::new(),
+            heading: 1,
         }
+    }
+
+    fn travel(&mut self, delta: isize) {
+        self.pos.horizontal += self.heading * delta;
+        self.pos.depth += self.pos.aim * delta.abs();
+    }
+}
 
-        self
+impl MovementModel for AimModel {
+    fn name(&self) -> &str {
+        "aim"
     }
 
-    pub fn change_pos_aim(mut self, command: &Command) -> Self {
+    fn apply(&mut self, command: &Command) {
         match command {
-            Command::Forward(delta) => {
-                self.horizontal += *delta as isize;
-                self.depth += self.aim * *delta as isize;
-            }
+            Command::Forward(delta) => self.travel(*delta as isize),
+            Command::Back(delta) => self.travel(-(*delta as isize)),
+            Command::Down(delta) => self.pos.aim += *delta as isize,
+            Command::Up(delta) => self.pos.aim -= *delta as isize,
+            Command::Turn => self.heading = -self.heading,
+            Command::SetAim(aim) => self.pos.aim = *aim,
+        }
+    }
 
-            Command::Down(delta) => {
-                self.aim += *delta as isize;
-            }
+    fn position(&self) -> &Position {
+        &self.pos
+    }
+}
 
-            Command::Up(delta) => {
-                self.aim -= *delta as isize;
-            }
+/// # Trajectory
+///
+/// Every position a model went through, starting from where it was
+/// before the first command.
+#[derive(Debug, Clone, Default)]
+struct Trajectory {
+    points: Vec<Position>,
+}
+
+impl Trajectory {
+    fn new(start: Position) -> Self {
+        Trajectory {
+            points: vec![start],
         }
+    }
+
+    fn push(&mut self, pos: Position) {
+        self.points.push(pos);
+    }
 
-        self
+    fn max_depth(&self) -> isize {
+        self.points.iter().map(|p| p.depth).max().unwrap_or(0)
+    }
+
+    // straight-line distance between each recorded position, summed up
+    fn total_distance(&self) -> f64 {
+        self.points
+            .windows(2)
+            .map(|w| {
+                let dx = (w[1].horizontal - w[0].horizontal) as f64;
+                let dy = (w[1].depth - w[0].depth) as f64;
+
+                (dx * dx + dy * dy).sqrt()
+            })
+            .sum()
+    }
+
+    // amount of commands after which the submarine was deeper than `depth`
+    fn time_below(&self, depth: isize) -> usize {
+        self.points
+            .iter()
+            .skip(1)
+            .filter(|p| p.depth > depth)
+            .count()
+    }
+
+    fn to_csv(&self) -> String {
+        let mut res = String::from("step,horizontal,depth,aim\n");
+        for (i, p) in self.points.iter().enumerate() {
+            res.push_str(&format!("{},{},{},{}\n", i, p.horizontal, p.depth, p.aim));
+        }
+
+        res
+    }
+
+    // depth goes downwards in SVG too, so no flipping is needed here
+    fn to_svg(&self) -> String {
+        let min_x = self.points.iter().map(|p| p.horizontal).min().unwrap_or(0);
+        let max_x = self.points.iter().map(|p| p.horizontal).max().unwrap_or(0);
+        let min_y = self.points.iter().map(|p| p.depth).min().unwrap_or(0);
+        let max_y = self.max_depth();
+
+        let points = self
+            .points
+            .iter()
+            .map(|p| format!("{},{}", p.horizontal, p.depth))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n\
+             <polyline fill=\"none\" stroke=\"black\" vector-effect=\"non-scaling-stroke\" points=\"{}\"/>\n\
+             </svg>\n",
+            min_x,
+            min_y,
+            (max_x - min_x).max(1),
+            (max_y - min_y).max(1),
+            points
+        )
+    }
+}
+
+/// Wraps a model, recording its position after every command.
+struct Recorded<M> {
+    model: M,
+    trajectory: Trajectory,
+}
+
+impl<M: MovementModel> Recorded<M> {
+    fn new(model: M) -> Self {
+        let trajectory = Trajectory::new(model.position().clone());
+
+        Recorded { model, trajectory }
+    }
+}
+
+impl<M: MovementModel> MovementModel for Recorded<M> {
+    fn name(&self) -> &str {
+        self.model.name()
+    }
+
+    fn apply(&mut self, command: &Command) {
+        self.model.apply(command);
+        self.trajectory.push(self.model.position().clone());
+    }
+
+    fn position(&self) -> &Position {
+        self.model.position()
+    }
+}
+
+// feeds every command to every model, only walking the commands once
+fn run_models<'a, I>(commands: I, models: &mut [&mut dyn MovementModel])
+where
+    I: IntoIterator<Item = &'a Command>,
+{
+    for cmd in commands {
+        for m in models.iter_mut() {
+            m.apply(cmd);
+        }
     }
 }
 
@@ -83,6 +377,20 @@ mod tests {
     use std::fs::File;
     use std::io::Read;
 
+    fn example() -> Vec<Command> {
+        [
+            "forward 5",
+            "down 5",
+            "forward 8",
+            "up 3",
+            "down 8",
+            "forward 2",
+        ]
+        .iter()
+        .map(|i| i.parse().unwrap())
+        .collect()
+    }
+
     #[test]
     fn test_day_two() {
         let mut test_file = File::open("res/day_two.input").unwrap();
@@ -90,20 +398,193 @@ mod tests {
 
         test_file.read_to_string(&mut test_string).unwrap();
 
-        let cmd_vec: Vec<Command> = test_string
-            .lines()
-            .map(|i| Command::parse(i.to_string()).unwrap())
-            .collect();
+        let cmd_vec = parse_program(&test_string).unwrap();
+
+        let mut direct = DirectModel::new();
+        let mut aim = AimModel::new();
+
+        run_models(&cmd_vec, &mut [&mut direct, &mut aim]);
+
+        println!("{}: {}", direct.name(), direct.position().product());
+        println!("{}: {}", aim.name(), aim.position().product());
+    }
+
+    #[test]
+    fn example_models() {
+        let mut direct = DirectModel::new();
+        let mut aim = AimModel::new();
+
+        run_models(&example(), &mut [&mut direct, &mut aim]);
+
+        assert_eq!(direct.position().product(), 150);
+        assert_eq!(aim.position().product(), 900);
+    }
+
+    #[test]
+    fn extended_commands() {
+        assert_eq!("back 3".parse(), Ok(Command::Back(3)));
+        assert_eq!("turn".parse(), Ok(Command::Turn));
+        assert_eq!("set aim -2".parse(), Ok(Command::SetAim(-2)));
+        assert!("sideways 2".parse::<Command>().is_err());
+        assert!("turn 2 3".parse::<Command>().is_err());
+
+        let mut aim = AimModel::new();
+        for cmd in [
+            Command::SetAim(2),
+            Command::Forward(3),
+            Command::Turn,
+            Command::Forward(1),
+            Command::Back(2),
+        ] {
+            aim.apply(&cmd);
+        }
+
+        assert_eq!(
+            aim.position(),
+            &Position {
+                horizontal: 4,
+                depth: 12,
+                aim: 2
+            }
+        );
+    }
+
+    #[test]
+    fn trajectory_recording() {
+        let mut aim = Recorded::new(AimModel::new());
+        for cmd in &example() {
+            aim.apply(cmd);
+        }
+
+        let t = &aim.trajectory;
+        assert_eq!(t.points.len(), 7);
+        assert_eq!(t.points[0], Position::new());
+        assert_eq!(t.points.last(), Some(aim.position()));
+        assert_eq!(t.max_depth(), 60);
+        assert_eq!(t.time_below(0), 4);
+        assert_eq!(t.time_below(40), 1);
+
+        // forward 5, forward 8 (+40 depth), forward 2 (+20 depth)
+        let expected = 5.0 + (64.0f64 + 1600.0).sqrt() + (4.0f64 + 400.0).sqrt();
+        assert!((t.total_distance() - expected).abs() < 1e-9);
+
+        let csv = t.to_csv();
+        assert_eq!(csv.lines().next(), Some("step,horizontal,depth,aim"));
+        assert_eq!(csv.lines().nth(4), Some("3,13,40,5"));
+        assert_eq!(csv.lines().last(), Some("6,15,60,10"));
+
+        let svg = t.to_svg();
+        assert!(svg.contains("viewBox=\"0 0 15 60\""));
+        assert!(svg.contains("points=\"0,0 5,0 5,0 13,40 13,40 13,40 15,60\""));
+    }
+
+    #[test]
+    fn recorded_in_single_pass() {
+        let mut direct = Recorded::new(DirectModel::new());
+        let mut aim = Recorded::new(AimModel::new());
 
-        let mut pos = Position::new();
-        let mut pos_aim = Position::new();
+        run_models(&example(), &mut [&mut direct, &mut aim]);
 
-        for cmd in cmd_vec {
-            pos = pos.change_pos(&cmd);
-            pos_aim = pos_aim.change_pos_aim(&cmd);
+        assert_eq!(direct.position().product(), 150);
+        assert_eq!(aim.position().product(), 900);
+        assert_eq!(direct.trajectory.max_depth(), 10);
+        assert_eq!(aim.trajectory.max_depth(), 60);
+
+        println!("{}", direct.trajectory.to_csv());
+        println!("{}", aim.trajectory.to_svg());
+    }
+
+    #[test]
+    fn command_round_trip() {
+        let cmds = [
+            Command::Forward(5),
+            Command::Down(2),
+            Command::Up(0),
+            Command::Back(12),
+            Command::Turn,
+            Command::SetAim(-3),
+        ];
+
+        for c in &cmds {
+            assert_eq!(c.to_string().parse::<Command>().as_ref(), Ok(c));
         }
 
-        println!("{}", pos.horizontal * pos.depth);
-        println!("{}", pos_aim.horizontal * pos_aim.depth);
+        assert_eq!("  FORWARD\t 5 ".parse(), Ok(Command::Forward(5)));
+        assert_eq!("Set  AIM 4".parse(), Ok(Command::SetAim(4)));
+        assert_eq!(Command::SetAim(4).to_string(), "set aim 4");
+    }
+
+    #[test]
+    fn command_errors() {
+        assert_eq!("".parse::<Command>(), Err(CommandError::Empty));
+        assert_eq!(
+            "sideways 2".parse::<Command>(),
+            Err(CommandError::UnknownCommand("sideways".into()))
+        );
+        assert_eq!(
+            "up".parse::<Command>(),
+            Err(CommandError::MissingUnits("up".into()))
+        );
+        assert_eq!(
+            "down -2".parse::<Command>(),
+            Err(CommandError::InvalidUnits("-2".into()))
+        );
+        assert_eq!(
+            "set depth 2".parse::<Command>(),
+            Err(CommandError::UnexpectedToken("depth".into()))
+        );
+        assert_eq!(
+            "turn around".parse::<Command>(),
+            Err(CommandError::UnexpectedToken("around".into()))
+        );
+
+        // tokens come back the way they were written
+        assert_eq!(
+            "SIDEWAYS 2".parse::<Command>(),
+            Err(CommandError::UnknownCommand("SIDEWAYS".into()))
+        );
+        assert_eq!(
+            "Up".parse::<Command>(),
+            Err(CommandError::MissingUnits("Up".into()))
+        );
+        assert_eq!(
+            "Set Aim".parse::<Command>(),
+            Err(CommandError::MissingUnits("Set Aim".into()))
+        );
+
+        let missing_aim = "SET".parse::<Command>().unwrap_err();
+        assert_eq!(
+            missing_aim,
+            CommandError::MissingToken("SET".into(), "aim".into())
+        );
+        assert_eq!(missing_aim.to_string(), "`SET` is missing `aim`");
+    }
+
+    #[test]
+    fn program_parsing() {
+        let program = "forward 5\n\ndown 5\nforward x\nup 3\nleft 2\n";
+        let errors = parse_program(program).unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                LineError {
+                    line: 4,
+                    error: CommandError::InvalidUnits("x".into())
+                },
+                LineError {
+                    line: 6,
+                    error: CommandError::UnknownCommand("left".into())
+                },
+            ]
+        );
+        assert_eq!(errors[0].to_string(), "line 4: invalid units `x`");
+
+        let text = example()
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>()
+            .join("\n");
+        assert_eq!(parse_program(&text), Ok(example()));
     }
 }
@@ -200,6 +200,330 @@ impl Table<u32> {
     }
 }
 
+/// # BitVec
+///
+/// Arbitrary-width bit vector. Bits are indexed by column, left to
+/// right, the same way they're written out - column 0 is the most
+/// significant bit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    bits: Vec<bool>,
+}
+
+impl BitVec {
+    pub fn new(width: usize) -> Self {
+        BitVec {
+            bits: vec![false; width],
+        }
+    }
+
+    /// Takes the lowest `width` bits of some word.
+    pub fn from_word<T: Into<u128>>(word: T, width: usize) -> Self {
+        let word = word.into();
+
+        BitVec {
+            bits: (0..width)
+                .rev()
+                .map(|n| n < 128 && (word >> n) & 1 == 1)
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn get(&self, column: usize) -> Option<bool> {
+        self.bits.get(column).copied()
+    }
+
+    pub fn set(&mut self, column: usize, bit: bool) {
+        self.bits[column] = bit;
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        self.bits.push(bit);
+    }
+
+    /// Iterates over every bit, left to right.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = bool> + '_ {
+        self.bits.iter().copied()
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().filter(|b| **b).count()
+    }
+
+    /// Only keeps the bits that are also set in `mask`. Anything past
+    /// the end of `mask` is cleared.
+    pub fn mask(&self, mask: &Self) -> Self {
+        BitVec {
+            bits: self
+                .iter()
+                .enumerate()
+                .map(|(i, b)| b && mask.get(i).unwrap_or(false))
+                .collect(),
+        }
+    }
+
+    pub fn invert(&self) -> Self {
+        BitVec {
+            bits: self.iter().map(|b| !b).collect(),
+        }
+    }
+
+    /// `None` if the set bits don't fit in a `u128`.
+    pub fn to_u128(&self) -> Option<u128> {
+        self.iter().try_fold(0u128, |acc, b| {
+            acc.checked_mul(2).map(|acc| acc | b as u128)
+        })
+    }
+
+    pub fn to_usize(&self) -> Option<usize> {
+        self.to_u128().and_then(|v| v.try_into().ok())
+    }
+
+    /// Counts the zeros and ones in each column across every word,
+    /// as `(zeros, ones)`. Words shorter than the widest one
+    /// just don't count towards the columns they're missing.
+    pub fn count_columns<'a, I: IntoIterator<Item = &'a Self>>(words: I) -> Vec<(usize, usize)> {
+        let mut counts: Vec<(usize, usize)> = Vec::new();
+
+        for w in words {
+            if counts.len() < w.len() {
+                counts.resize(w.len(), (0, 0));
+            }
+
+            for (c, b) in counts.iter_mut().zip(w.iter()) {
+                if b {
+                    c.1 += 1;
+                } else {
+                    c.0 += 1;
+                }
+            }
+        }
+
+        counts
+    }
+}
+
+impl std::str::FromStr for BitVec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim()
+            .chars()
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(format!("invalid bit: {}", c)),
+            })
+            .collect::<Result<Vec<bool>, String>>()
+            .map(|bits| BitVec { bits })
+    }
+}
+
+impl fmt::Display for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.iter() {
+            write!(f, "{}", if b { '1' } else { '0' })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// # Rng
+///
+/// Small deterministic PRNG (SplitMix64), so anything random can be
+/// reproduced from its seed without pulling in a crate for it.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`. Rejects the biased tail instead of just taking
+    /// the modulo.
+    pub fn gen_range(&mut self, n: usize) -> usize {
+        if n == 0 {
+            panic!("empty range");
+        }
+
+        let n = n as u64;
+        let zone = u64::MAX - (u64::MAX % n);
+        loop {
+            let v = self.next_u64();
+            if v < zone {
+                return (v % n) as usize;
+            }
+        }
+    }
+
+    /// Fisher-Yates, in place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.gen_range(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// # BigUint
+///
+/// Unsigned integer of any size, for when even u128 isn't enough. Only
+/// does what's needed so far: adding, multiplying and printing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BigUint {
+    // little endian, without trailing zero limbs (so zero is empty)
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+
+        Some(
+            self.limbs
+                .iter()
+                .rev()
+                .fold(0u128, |n, l| (n << 32) | *l as u128),
+        )
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    // divides in place, giving back the remainder
+    fn div_rem_small(&mut self, d: u32) -> u32 {
+        let mut rem = 0u64;
+        for l in self.limbs.iter_mut().rev() {
+            let cur = (rem << 32) | *l as u64;
+            *l = (cur / d as u64) as u32;
+            rem = cur % d as u64;
+        }
+
+        self.trim();
+        rem as u32
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(mut n: u128) -> Self {
+        let mut limbs = Vec::new();
+        while n > 0 {
+            limbs.push(n as u32);
+            n >>= 32;
+        }
+
+        BigUint { limbs }
+    }
+}
+
+impl std::ops::Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = 0u64;
+
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let a = self.limbs.get(i).copied().unwrap_or(0) as u64;
+            let b = other.limbs.get(i).copied().unwrap_or(0) as u64;
+            let sum = a + b + carry;
+
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+
+        if carry > 0 {
+            limbs.push(carry as u32);
+        }
+
+        BigUint { limbs }
+    }
+}
+
+impl std::ops::Mul for &BigUint {
+    type Output = BigUint;
+
+    // schoolbook, which is plenty for the sizes this gets used with
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+
+            for (j, b) in other.limbs.iter().enumerate() {
+                let cur = limbs[i + j] as u64 + *a as u64 * *b as u64 + carry;
+                limbs[i + j] = cur as u32;
+                carry = cur >> 32;
+            }
+
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+
+        let mut n = BigUint { limbs };
+        n.trim();
+        n
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // nine decimal digits at a time, lowest first
+        let mut n = self.clone();
+        let mut chunks = Vec::new();
+        while !n.is_zero() {
+            chunks.push(n.div_rem_small(1_000_000_000));
+        }
+
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for c in chunks.iter().rev() {
+            write!(f, "{:09}", c)?;
+        }
+
+        Ok(())
+    }
+}
+
 pub fn coords_to_table(coords: &mut [Coordinate], mark: char, empty: char) -> Table<char> {
     let mut t = Table::new();
     // get the max X of our coordinates (this will dictate
@@ -248,4 +572,95 @@ mod tests {
 
         println!("{:?}", table.get_elems_around(1, 1));
     }
+
+    #[test]
+    fn test_bitvec() {
+        let bits = "0000000000000000000000000000000000000000000000000000000000000000000010110"
+            .parse::<BitVec>()
+            .unwrap();
+
+        assert_eq!(bits.len(), 73);
+        assert_eq!(bits.to_usize(), Some(22));
+        assert_eq!(bits.count_ones(), 3);
+        assert_eq!(bits.get(68), Some(true));
+        assert_eq!(bits.get(73), None);
+
+        let wide = BitVec::from_word(u128::MAX, 130);
+        assert_eq!(wide.to_u128(), Some(u128::MAX));
+        assert_eq!(wide.count_ones(), 128);
+
+        let mut wider = wide.clone();
+        wider.set(1, true);
+        assert_eq!(wider.to_u128(), None);
+        assert!("10x1".parse::<BitVec>().is_err());
+    }
+
+    #[test]
+    fn test_bitvec_masking() {
+        let word = BitVec::from_word(0b1011u8, 4);
+        let mask = "0110".parse::<BitVec>().unwrap();
+
+        assert_eq!(word.to_string(), "1011");
+        assert_eq!(word.mask(&mask).to_string(), "0010");
+        assert_eq!(word.invert().to_string(), "0100");
+        assert_eq!(BitVec::from_word(1u8, 8).iter().next_back(), Some(true));
+    }
+
+    #[test]
+    fn test_bitvec_columns() {
+        let words = ["101", "001", "11"]
+            .iter()
+            .map(|w| w.parse::<BitVec>().unwrap())
+            .collect::<Vec<BitVec>>();
+
+        assert_eq!(BitVec::count_columns(&words), vec![(1, 2), (2, 1), (0, 2)]);
+    }
+
+    #[test]
+    fn test_rng() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let seq = (0..16).map(|_| a.next_u64()).collect::<Vec<u64>>();
+
+        assert_eq!(seq, (0..16).map(|_| b.next_u64()).collect::<Vec<u64>>());
+        assert_ne!(seq[0], Rng::new(43).next_u64());
+        assert!((0..1000).all(|_| a.gen_range(7) < 7));
+
+        let mut items = (0..50).collect::<Vec<usize>>();
+        a.shuffle(&mut items);
+        assert_ne!(items, (0..50).collect::<Vec<usize>>());
+
+        items.sort_unstable();
+        assert_eq!(items, (0..50).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_biguint() {
+        let a = BigUint::from(u128::MAX);
+        let b = BigUint::from(1u128);
+
+        assert_eq!(BigUint::from(0u128), BigUint::zero());
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(a.to_u128(), Some(u128::MAX));
+        assert_eq!(a.to_string(), u128::MAX.to_string());
+
+        let sum = &a + &b;
+        assert_eq!(sum.to_u128(), None);
+        assert_eq!(sum.to_string(), "340282366920938463463374607431768211456");
+
+        let n = BigUint::from(1234567890123456789u128);
+        let m = BigUint::from(987654321987654321u128);
+        assert_eq!(
+            (&n * &m).to_u128(),
+            Some(1234567890123456789u128 * 987654321987654321u128)
+        );
+        assert!((&n * &BigUint::zero()).is_zero());
+
+        let factorial = (1..=30u128).fold(BigUint::from(1u128), |f, i| &f * &BigUint::from(i));
+        assert_eq!(factorial.to_string(), "265252859812191058636308480000000");
+        assert_eq!(
+            (&factorial * &factorial).to_string(),
+            "70359079638545882374689246780656119576032161719910400000000000000"
+        );
+    }
 }